@@ -0,0 +1,47 @@
+use super::*;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Backend {
+  Git,
+  #[cfg(feature = "gix")]
+  Gix,
+}
+
+impl Backend {
+  pub(crate) fn from_env() -> Result<Self> {
+    let Some(value) = env::var_os("WT_BACKEND") else {
+      return Ok(Self::default());
+    };
+
+    value
+      .to_str()
+      .ok_or_else(|| anyhow!("`WT_BACKEND` is not valid unicode"))?
+      .parse()
+      .context("invalid `WT_BACKEND`")
+  }
+}
+
+impl Default for Backend {
+  fn default() -> Self {
+    #[cfg(feature = "gix")]
+    return Self::Gix;
+
+    #[cfg(not(feature = "gix"))]
+    return Self::Git;
+  }
+}
+
+impl FromStr for Backend {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "git" => Ok(Self::Git),
+      #[cfg(feature = "gix")]
+      "gix" => Ok(Self::Gix),
+      #[cfg(not(feature = "gix"))]
+      "gix" => bail!("`wt` was built without the `gix` feature"),
+      _ => bail!("unknown backend `{s}`, expected `git` or `gix`"),
+    }
+  }
+}
@@ -0,0 +1,35 @@
+use super::*;
+
+#[cfg(unix)]
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+#[cfg(unix)]
+pub(crate) fn from_bytes(bytes: &[u8]) -> OsString {
+  OsString::from_vec(bytes.to_vec())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn from_bytes(bytes: &[u8]) -> OsString {
+  String::from_utf8_lossy(bytes).into_owned().into()
+}
+
+pub(crate) fn print_path(path: &Path) -> io::Result<()> {
+  let mut stdout = io::stdout().lock();
+
+  stdout.write_all(&to_bytes(path.as_os_str()))?;
+
+  stdout.write_all(b"\n")
+}
+
+#[cfg(unix)]
+pub(crate) fn to_bytes(value: &OsStr) -> Cow<'_, [u8]> {
+  Cow::Borrowed(value.as_bytes())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn to_bytes(value: &OsStr) -> Cow<'_, [u8]> {
+  match value.to_string_lossy() {
+    Cow::Borrowed(value) => Cow::Borrowed(value.as_bytes()),
+    Cow::Owned(value) => Cow::Owned(value.into_bytes()),
+  }
+}
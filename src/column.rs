@@ -0,0 +1,43 @@
+use super::*;
+
+#[derive(
+  Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Column {
+  Activity,
+  Age,
+  Ahead,
+  Branch,
+  Diff,
+  Head,
+  Marker,
+  Path,
+  Sparse,
+  State,
+  Subject,
+}
+
+impl Column {
+  pub(crate) const DEFAULT: &[Self] = &[
+    Self::Marker,
+    Self::Branch,
+    Self::Head,
+    Self::Diff,
+    Self::Age,
+    Self::Subject,
+    Self::Sparse,
+    Self::State,
+    Self::Path,
+  ];
+
+  pub(crate) fn code(self) -> Option<&'static str> {
+    match self {
+      Self::Branch => Some(style::BOLD),
+      Self::Head => Some(style::CYAN),
+      Self::Marker => Some(style::GREEN),
+      Self::State => Some(style::RED),
+      _ => None,
+    }
+  }
+}
@@ -1,12 +1,42 @@
-use super::*;
+use {super::*, std::collections::BTreeMap};
+
+const REPO_PATH: &str = "wt/config.toml";
 
 #[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub(crate) struct Config {
+  #[serde(default)]
+  pub(crate) create: Create,
   #[serde(default)]
   pub(crate) hooks: Hooks,
+  #[serde(default)]
+  pub(crate) list: List,
+  #[serde(default)]
+  pub(crate) sparse: BTreeMap<String, Vec<String>>,
+  #[serde(default)]
+  pub(crate) sync: Sync,
+  #[serde(default)]
+  pub(crate) trash: Trash,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Create {
+  pub(crate) clone_dirs: Vec<PathBuf>,
+  pub(crate) copy: Vec<FileEntry>,
+  pub(crate) lfs: bool,
+  pub(crate) link: Vec<FileEntry>,
+  pub(crate) submodules: bool,
 }
 
 #[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct FileEntry {
+  pub(crate) pattern: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub(crate) struct HookEntry {
   pub(crate) command: String,
   #[serde(default)]
@@ -14,29 +44,224 @@ pub(crate) struct HookEntry {
 }
 
 #[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub(crate) struct Hooks {
   #[serde(default)]
   pub(crate) post_worktree_change: Vec<HookEntry>,
 }
 
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct List {
+  #[serde(default)]
+  pub(crate) columns: Option<Vec<Column>>,
+  #[serde(default)]
+  pub(crate) path: PathStyle,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Sync {
+  #[serde(default)]
+  pub(crate) base: Option<String>,
+  #[serde(default)]
+  pub(crate) strategy: Strategy,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Trash {
+  pub(crate) enabled: bool,
+  pub(crate) retention_days: u64,
+}
+
 impl Config {
+  pub(crate) fn contents(path: &Path) -> Result<String> {
+    match fs::read_to_string(path) {
+      Ok(contents) => Ok(contents),
+      Err(error) if error.kind() == io::ErrorKind::NotFound => {
+        Ok(String::new())
+      }
+      Err(error) => Err(
+        Error::from(error)
+          .context(format!("failed to read `{}`", path.display())),
+      ),
+    }
+  }
+
   pub(crate) fn load() -> Result<Self> {
-    Ok(confy::load("wt", "config")?)
+    let mut table = toml::Table::new();
+
+    for path in Self::paths()? {
+      let contents = Self::contents(&path)?;
+
+      Self::parse(&path, &contents)?;
+
+      merge(&mut table, contents.parse()?);
+    }
+
+    toml::Value::Table(table)
+      .try_into()
+      .context("invalid config")
+  }
+
+  pub(crate) fn lookup<'a>(
+    table: &'a toml::Table,
+    key: &str,
+  ) -> Option<&'a toml::Value> {
+    let mut segments = key.split('.');
+
+    let mut value = table.get(segments.next()?)?;
+
+    for segment in segments {
+      value = value.as_table()?.get(segment)?;
+    }
+
+    Some(value)
+  }
+
+  pub(crate) fn parse(path: &Path, contents: &str) -> Result<Self> {
+    toml::from_str(contents)
+      .map_err(|error| {
+        let location = error.span().map_or_else(String::new, |span| {
+          let (line, column) = position(contents, span.start);
+          format!(":{line}:{column}")
+        });
+
+        anyhow!(
+          "{}{location}: {}",
+          path.display(),
+          error.message().trim_end()
+        )
+      })
+      .context("invalid config")
+  }
+
+  pub(crate) fn path() -> Result<PathBuf> {
+    Ok(confy::get_configuration_file_path("wt", "config")?)
+  }
+
+  fn paths() -> Result<Vec<PathBuf>> {
+    Ok(
+      [Self::path()?]
+        .into_iter()
+        .chain(Self::repo_path().ok())
+        .collect(),
+    )
+  }
+
+  pub(crate) fn repo_path() -> Result<PathBuf> {
+    Ok(Repository::discover()?.common_dir().join(REPO_PATH))
+  }
+
+  pub(crate) fn source(key: &str) -> Result<PathBuf> {
+    let mut source = Self::path()?;
+
+    for path in Self::paths()? {
+      if Self::lookup(&Self::contents(&path)?.parse()?, key).is_some() {
+        source = path;
+      }
+    }
+
+    Ok(source)
+  }
+
+  pub(crate) fn sparse_profile(&self, name: &str) -> Result<&[String]> {
+    self
+      .sparse
+      .get(name)
+      .map(Vec::as_slice)
+      .ok_or_else(|| anyhow!("unknown sparse profile `{name}`"))
+  }
+}
+
+impl FileEntry {
+  pub(crate) fn matches(&self, path: &Path) -> Result<bool> {
+    let pattern = glob::Pattern::new(&self.pattern)
+      .with_context(|| format!("invalid pattern `{}`", self.pattern))?;
+
+    if self.pattern.contains('/') {
+      return Ok(pattern.matches_path(path));
+    }
+
+    Ok(
+      path
+        .file_name()
+        .is_some_and(|name| pattern.matches(&name.to_string_lossy())),
+    )
   }
 }
 
 impl HookEntry {
-  pub(crate) fn matches(&self) -> Result<bool> {
+  pub(crate) fn matches(&self, directory: &Path) -> Result<bool> {
     let Some(pattern) = &self.only_if else {
       return Ok(true);
     };
 
-    let cwd = env::current_dir()?;
+    glob::Pattern::new(pattern)
+      .with_context(|| format!("invalid `only_if` pattern `{pattern}`"))?;
 
     Ok(
-      glob::glob(cwd.join(pattern).to_string_lossy().as_ref())?
+      glob::glob(directory.join(pattern).to_string_lossy().as_ref())?
         .next()
         .is_some(),
     )
   }
 }
+
+impl Hooks {
+  pub(crate) fn events(&self) -> [(&'static str, &[HookEntry]); 1] {
+    [("post-worktree-change", &self.post_worktree_change)]
+  }
+}
+
+impl Default for Create {
+  fn default() -> Self {
+    Self {
+      clone_dirs: Vec::new(),
+      copy: Vec::new(),
+      lfs: true,
+      link: Vec::new(),
+      submodules: true,
+    }
+  }
+}
+
+impl Default for Trash {
+  fn default() -> Self {
+    Self {
+      enabled: false,
+      retention_days: 7,
+    }
+  }
+}
+
+fn merge(base: &mut toml::Table, overlay: toml::Table) {
+  for (key, value) in overlay {
+    match (base.get_mut(&key), value) {
+      (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => {
+        merge(base, overlay);
+      }
+      (Some(slot), value) => *slot = value,
+      (None, value) => {
+        base.insert(key, value);
+      }
+    }
+  }
+}
+
+fn position(contents: &str, offset: usize) -> (usize, usize) {
+  let before = contents.get(..offset).unwrap_or(contents);
+
+  let line = before.matches('\n').count() + 1;
+
+  let column = before
+    .rsplit('\n')
+    .next()
+    .unwrap_or_default()
+    .chars()
+    .count()
+    + 1;
+
+  (line, column)
+}
@@ -0,0 +1,308 @@
+use super::*;
+
+const DIRECTORY: &str = "wt/journal";
+
+const LOG: &str = "wt/delete.log";
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Entry {
+  pub(crate) branch: Option<OsString>,
+  pub(crate) head: Option<String>,
+  pub(crate) locked: Option<String>,
+  pub(crate) path: PathBuf,
+  pub(crate) trash: PathBuf,
+}
+
+#[derive(Debug)]
+pub(crate) struct Journal {
+  path: PathBuf,
+}
+
+impl Entry {
+  pub(crate) fn name(&self) -> Cow<'_, str> {
+    self
+      .branch
+      .as_ref()
+      .map_or(Cow::Borrowed("(detached)"), |branch| {
+        branch.to_string_lossy()
+      })
+  }
+
+  pub(crate) fn parse(contents: &[u8]) -> Vec<Self> {
+    contents
+      .split(|&byte| byte == 0)
+      .collect::<Vec<_>>()
+      .split(|line| line.is_empty())
+      .filter(|record| !record.is_empty())
+      .map(|record| {
+        let mut entry = Self::default();
+
+        for line in record {
+          let (key, value) = line
+            .iter()
+            .position(|&byte| byte == b' ')
+            .map_or((*line, [].as_slice()), |i| (&line[..i], &line[i + 1..]));
+
+          let text = || String::from_utf8_lossy(value).into_owned();
+
+          match key {
+            b"branch" => entry.branch = Some(bytes::from_bytes(value)),
+            b"head" => entry.head = Some(text()),
+            b"locked" => entry.locked = Some(text()),
+            b"path" => entry.path = bytes::from_bytes(value).into(),
+            b"trash" => entry.trash = bytes::from_bytes(value).into(),
+            _ => {}
+          }
+        }
+
+        entry
+      })
+      .collect()
+  }
+
+  fn recover(&self, repository: &Repository, style: Style) -> Result {
+    let trash = Trash::interrupted(repository, self);
+
+    if self.trash.exists() || !self.path.exists() {
+      repository.prune()?;
+
+      match &trash {
+        Some(trash) if self.trash.exists() => trash.record(repository)?,
+        Some(trash) => trash.discard(repository)?,
+        None if self.trash.exists() => {
+          fs::remove_dir_all(&self.trash).with_context(|| {
+            format!("failed to delete `{}`", self.trash.display())
+          })?;
+        }
+        None => {}
+      }
+
+      if let Some(branch) = &self.branch {
+        let head = repository
+          .at(repository.common_dir())
+          .resolve(&format!("refs/heads/{}", branch.to_string_lossy()))
+          .ok();
+
+        if head.is_some()
+          && (self.head.is_none() || head.as_ref() == self.head.as_ref())
+        {
+          repository.delete_branch(branch)?;
+        }
+      }
+
+      eprintln!(
+        "{} interrupted removal of worktree {}",
+        style.apply(style::GREEN, "finished"),
+        style.apply(style::BOLD, self.name()),
+      );
+    } else {
+      if let Some(trash) = &trash {
+        trash.discard(repository)?;
+      }
+
+      if let Some(reason) = &self.locked {
+        let locked = repository.worktrees()?.into_iter().any(|worktree| {
+          worktree.path == self.path && worktree.locked.is_some()
+        });
+
+        if !locked {
+          repository.lock_worktree(
+            &self.path,
+            Some(reason.as_str()).filter(|reason| !reason.is_empty()),
+          )?;
+        }
+      }
+
+      eprintln!(
+        "{} interrupted removal of worktree {}",
+        style.apply(style::GREEN, "rolled back"),
+        style.apply(style::BOLD, self.name()),
+      );
+    }
+
+    Ok(())
+  }
+
+  pub(crate) fn serialize(&self, buffer: &mut Vec<u8>) {
+    let mut field = |key: &str, value: &[u8]| {
+      buffer.extend_from_slice(key.as_bytes());
+      buffer.push(b' ');
+      buffer.extend_from_slice(value);
+      buffer.push(0);
+    };
+
+    field("path", &bytes::to_bytes(self.path.as_os_str()));
+    field("trash", &bytes::to_bytes(self.trash.as_os_str()));
+
+    if let Some(branch) = &self.branch {
+      field("branch", &bytes::to_bytes(branch));
+    }
+
+    if let Some(head) = &self.head {
+      field("head", head.as_bytes());
+    }
+
+    if let Some(locked) = &self.locked {
+      field("locked", locked.as_bytes());
+    }
+
+    buffer.push(0);
+  }
+}
+
+impl Journal {
+  pub(crate) fn create(
+    repository: &Repository,
+    entries: &[Entry],
+  ) -> Result<Self> {
+    let directory = repository.common_dir().join(DIRECTORY);
+
+    fs::create_dir_all(&directory)
+      .with_context(|| format!("failed to create `{}`", directory.display()))?;
+
+    let path = directory.join(process::id().to_string());
+
+    let mut contents = Vec::new();
+
+    for entry in entries {
+      entry.serialize(&mut contents);
+    }
+
+    let temporary = path.with_extension("tmp");
+
+    fs::write(&temporary, contents)
+      .and_then(|()| fs::rename(&temporary, &path))
+      .with_context(|| format!("failed to write `{}`", path.display()))?;
+
+    Ok(Self { path })
+  }
+
+  pub(crate) fn failures(repository: &Repository) -> Vec<String> {
+    let log = Self::log(repository);
+
+    let Ok(contents) = fs::read_to_string(&log) else {
+      return Vec::new();
+    };
+
+    let _ = fs::remove_file(&log);
+
+    contents.lines().map(str::to_owned).collect()
+  }
+
+  pub(crate) fn finish(self) -> Result {
+    fs::remove_file(&self.path)
+      .with_context(|| format!("failed to remove `{}`", self.path.display()))?;
+
+    if let Some(directory) = self.path.parent() {
+      let _ = fs::remove_dir(directory);
+    }
+
+    Ok(())
+  }
+
+  pub(crate) fn log(repository: &Repository) -> PathBuf {
+    repository.common_dir().join(LOG)
+  }
+
+  pub(crate) fn recover(repository: &Repository) -> Result<usize> {
+    let style = Style::stderr();
+
+    let directory = repository.common_dir().join(DIRECTORY);
+
+    let Ok(entries) = fs::read_dir(&directory) else {
+      return Ok(0);
+    };
+
+    let mut journals = entries
+      .filter_map(|entry| {
+        let path = entry.ok()?.path();
+        let pid = path.file_name()?.to_str()?.parse::<u32>().ok()?;
+        Some((pid, path))
+      })
+      .collect::<Vec<_>>();
+
+    journals.sort();
+
+    let mut recovered = 0;
+
+    for (pid, path) in journals {
+      if pid != process::id() && running(pid) {
+        continue;
+      }
+
+      for entry in Entry::parse(&fs::read(&path)?) {
+        entry.recover(repository, style)?;
+        recovered += 1;
+      }
+
+      fs::remove_file(&path)?;
+    }
+
+    let _ = fs::remove_dir(&directory);
+
+    Ok(recovered)
+  }
+
+  pub(crate) fn resume(repository: &Repository) {
+    let style = Style::stderr();
+
+    for failure in Self::failures(repository) {
+      eprintln!("{}: {failure}", style.apply(style::YELLOW, "warning"));
+    }
+
+    if !repository.common_dir().join(DIRECTORY).is_dir() {
+      return;
+    }
+
+    if let Err(error) = Self::recover(repository) {
+      eprintln!(
+        "{}: failed to recover interrupted removal: {error:#}",
+        style.apply(style::YELLOW, "warning"),
+      );
+    }
+  }
+}
+
+fn running(pid: u32) -> bool {
+  let Ok(pid) = libc::pid_t::try_from(pid) else {
+    return false;
+  };
+
+  // SAFETY: signal 0 only checks whether `pid` exists and can be signaled.
+  if unsafe { libc::kill(pid, 0) } == 0 {
+    return true;
+  }
+
+  io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn round_trip() {
+    let entries = [
+      Entry {
+        branch: Some("feature".into()),
+        head: Some("abc123".into()),
+        locked: Some("usb drive".into()),
+        path: "/tmp/project.feature".into(),
+        trash: "/tmp/.wt-removing-1-0".into(),
+      },
+      Entry {
+        path: "/tmp/project.detached".into(),
+        trash: "/tmp/.wt-removing-1-1".into(),
+        ..Entry::default()
+      },
+    ];
+
+    let mut contents = Vec::new();
+
+    for entry in &entries {
+      entry.serialize(&mut contents);
+    }
+
+    assert_eq!(Entry::parse(&contents), entries);
+  }
+}
@@ -1,35 +1,61 @@
 use {
-  anyhow::{Error, anyhow, bail},
+  anyhow::{Context, Error, anyhow, bail},
   arguments::Arguments,
+  backend::Backend,
   clap::Parser,
+  column::Column,
   config::Config,
+  path_style::PathStyle,
+  provision::Provision,
+  repository::{Checkout, Repository},
   serde::{Deserialize, Serialize},
   std::{
+    borrow::Cow,
     env,
+    ffi::{OsStr, OsString},
     fmt::{self, Display, Formatter},
-    io::{self, IsTerminal},
-    path::Path,
+    fs,
+    io::{self, IsTerminal, Write},
+    mem,
+    path::{Path, PathBuf},
     process::{self, Command, Stdio},
-    str,
+    str::{self, FromStr},
+    thread,
   },
+  strategy::Strategy,
   style::Style,
   subcommand::Subcommand,
+  template::Template,
   worktree::Worktree,
 };
 
 #[cfg(unix)]
 use {
+  journal::Journal,
   skim::{
     Skim, SkimItem, SkimItemReceiver, SkimItemSender,
     options::SkimOptionsBuilder, prelude::unbounded,
   },
-  std::{borrow::Cow, fs, path::PathBuf, sync::Arc, thread},
+  std::{collections::HashSet, os::unix::process::CommandExt, sync::Arc},
+  trash::Trash,
 };
 
 mod arguments;
+mod backend;
+mod bytes;
+mod column;
 mod config;
+#[cfg(unix)]
+mod journal;
+mod path_style;
+mod provision;
+mod repository;
+mod strategy;
 mod style;
 mod subcommand;
+mod template;
+#[cfg(unix)]
+mod trash;
 mod worktree;
 
 type Result<T = (), E = Error> = std::result::Result<T, E>;
@@ -41,7 +67,15 @@ fn main() {
     let causes = error.chain().skip(1).count();
 
     for (i, err) in error.chain().skip(1).enumerate() {
-      eprintln!("       {}─ {err}", if i < causes - 1 { '├' } else { '└' });
+      let last = i == causes - 1;
+
+      for (j, line) in err.to_string().lines().enumerate() {
+        if j == 0 {
+          eprintln!("       {}─ {line}", if last { '└' } else { '├' });
+        } else {
+          eprintln!("       {}  {line}", if last { ' ' } else { '│' });
+        }
+      }
     }
 
     process::exit(1);
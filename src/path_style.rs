@@ -0,0 +1,31 @@
+use super::*;
+
+#[derive(
+  Clone, Copy, Debug, Default, Deserialize, Serialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum PathStyle {
+  /// Absolute paths.
+  #[default]
+  Absolute,
+  /// Paths relative to the home directory, prefixed with `~`.
+  Home,
+  /// Paths relative to the directory containing the main worktree.
+  Parent,
+}
+
+impl PathStyle {
+  pub(crate) fn apply(self, path: &Path, parent: Option<&Path>) -> PathBuf {
+    let relative = match self {
+      Self::Absolute => None,
+      Self::Home => env::var_os("HOME")
+        .and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf))
+        .map(|relative| Path::new("~").join(relative)),
+      Self::Parent => parent
+        .and_then(|parent| path.strip_prefix(parent).ok())
+        .map(Path::to_path_buf),
+    };
+
+    relative.unwrap_or_else(|| path.to_path_buf())
+  }
+}
@@ -0,0 +1,443 @@
+use {
+  super::*,
+  std::{iter::once, time::Instant},
+};
+
+#[derive(Clone, Copy)]
+enum Action {
+  Copy,
+  Link,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Method {
+  Hardlink,
+  #[default]
+  Reflink,
+}
+
+pub(crate) struct Provision<'a> {
+  config: &'a config::Create,
+  repository: &'a Repository,
+  source: Option<PathBuf>,
+  style: Style,
+}
+
+#[derive(Default)]
+struct Tally {
+  hardlinked: u64,
+  method: Method,
+  reflinked: u64,
+  symlinks: usize,
+}
+
+impl Action {
+  fn apply(self, from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    match self {
+      Self::Copy => copy(from, to),
+      Self::Link => symlink(from, to),
+    }
+  }
+
+  fn past(self) -> &'static str {
+    match self {
+      Self::Copy => "copied",
+      Self::Link => "linked",
+    }
+  }
+
+  fn present(self) -> &'static str {
+    match self {
+      Self::Copy => "copy",
+      Self::Link => "link",
+    }
+  }
+}
+
+impl<'a> Provision<'a> {
+  pub(crate) fn apply(&self, destination: &Path) -> Result {
+    if self.config.submodules {
+      self.submodules(destination);
+    }
+
+    if self.config.lfs {
+      self.lfs(destination);
+    }
+
+    let Some(source) = &self.source else {
+      return Ok(());
+    };
+
+    self.ignored(source, destination)?;
+
+    for directory in &self.config.clone_dirs {
+      self.clone(source, destination, directory);
+    }
+
+    Ok(())
+  }
+
+  fn clone(&self, source: &Path, destination: &Path, directory: &Path) {
+    let from = source.join(directory);
+
+    let to = destination.join(directory);
+
+    if !from.is_dir() || to.symlink_metadata().is_ok() {
+      return;
+    }
+
+    let start = Instant::now();
+
+    let mut tally = Tally::default();
+
+    let result = to
+      .parent()
+      .map_or(Ok(()), fs::create_dir_all)
+      .and_then(|()| clone(&from, &to, &mut tally));
+
+    match result {
+      Ok(()) => eprintln!(
+        "{} {} in {:.2}s{tally}",
+        self.style.apply(style::GREEN, "cloned"),
+        self.style.apply(style::CYAN, directory.display()),
+        start.elapsed().as_secs_f64(),
+      ),
+      Err(error) => {
+        let _ = fs::remove_dir_all(&to);
+
+        self.warn(format_args!(
+          "skipped cloning `{}`, nothing was copied: {error}",
+          directory.display(),
+        ));
+      }
+    }
+  }
+
+  fn ignored(&self, source: &Path, destination: &Path) -> Result {
+    if self.config.copy.is_empty() && self.config.link.is_empty() {
+      return Ok(());
+    }
+
+    for path in self.repository.at(source).ignored()? {
+      let action = if matches(&self.config.link, &path)? {
+        Action::Link
+      } else if matches(&self.config.copy, &path)? {
+        Action::Copy
+      } else {
+        continue;
+      };
+
+      let to = destination.join(&path);
+
+      if to.symlink_metadata().is_ok() {
+        continue;
+      }
+
+      match action.apply(&source.join(&path), &to) {
+        Ok(()) => eprintln!(
+          "{} {}",
+          self.style.apply(style::GREEN, action.past()),
+          self.style.apply(style::CYAN, path.display()),
+        ),
+        Err(error) => eprintln!(
+          "{}: failed to {} `{}`: {error}",
+          self.style.apply(style::YELLOW, "warning"),
+          action.present(),
+          path.display(),
+        ),
+      }
+    }
+
+    Ok(())
+  }
+
+  fn lfs(&self, destination: &Path) {
+    match self.repository.at(destination).lfs_tracked() {
+      Ok(true) => {}
+      Ok(false) => return,
+      Err(error) => {
+        self.warn(format_args!("failed to detect LFS files: {error}"));
+        return;
+      }
+    }
+
+    let start = Instant::now();
+
+    match self.repository.at(destination).lfs_pull() {
+      Ok(()) => eprintln!(
+        "{} LFS objects in {:.2}s",
+        self.style.apply(style::GREEN, "pulled"),
+        start.elapsed().as_secs_f64(),
+      ),
+      Err(error) => self.warn(format_args!(
+        "failed to pull LFS objects: {}",
+        last_line(&error)
+      )),
+    }
+  }
+
+  pub(crate) fn new(
+    config: &'a config::Create,
+    repository: &'a Repository,
+    style: Style,
+  ) -> Self {
+    let source = (!config.clone_dirs.is_empty()
+      || !config.copy.is_empty()
+      || !config.link.is_empty())
+    .then(|| {
+      repository
+        .find_worktree(None)
+        .or_else(|_| repository.main_worktree())
+        .ok()
+    })
+    .flatten()
+    .filter(|worktree| !worktree.bare)
+    .map(|worktree| worktree.path);
+
+    Self {
+      config,
+      repository,
+      source,
+      style,
+    }
+  }
+
+  fn reference(&self, name: &str) -> Option<PathBuf> {
+    let worktrees =
+      fs::read_dir(self.repository.common_dir().join("worktrees"))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()));
+
+    once(self.repository.common_dir().to_owned())
+      .chain(worktrees)
+      .map(|directory| directory.join("modules").join(name))
+      .find(|directory| directory.join("objects").is_dir())
+  }
+
+  fn submodules(&self, destination: &Path) {
+    if !destination.join(".gitmodules").is_file() {
+      return;
+    }
+
+    let checkout = self.repository.at(destination);
+
+    let submodules = checkout.submodules();
+
+    for (i, (name, path)) in submodules.iter().enumerate() {
+      let start = Instant::now();
+
+      let reference = self.reference(name);
+
+      match checkout.update_submodule(path, reference.as_deref()) {
+        Ok(()) => eprintln!(
+          "{} submodule {} ({}/{}) in {:.2}s{}",
+          self.style.apply(style::GREEN, "initialized"),
+          self.style.apply(style::CYAN, path.display()),
+          i + 1,
+          submodules.len(),
+          start.elapsed().as_secs_f64(),
+          if reference.is_some() {
+            ", reusing existing objects"
+          } else {
+            ""
+          },
+        ),
+        Err(error) => self.warn(format_args!(
+          "failed to initialize submodule `{}`: {}",
+          path.display(),
+          last_line(&error)
+        )),
+      }
+    }
+  }
+
+  fn warn(&self, message: fmt::Arguments) {
+    eprintln!("{}: {message}", self.style.apply(style::YELLOW, "warning"));
+  }
+}
+
+impl Display for Tally {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    let shared = [(self.reflinked, "reflinks"), (self.hardlinked, "hardlinks")]
+      .into_iter()
+      .filter(|(bytes, _)| *bytes > 0)
+      .map(|(bytes, method)| format!("{} via {method}", size(bytes)))
+      .collect::<Vec<String>>();
+
+    if !shared.is_empty() {
+      write!(f, ", sharing {}", shared.join(" and "))?;
+    }
+
+    if self.symlinks > 0 {
+      write!(
+        f,
+        ", recreating {} symlink{}",
+        self.symlinks,
+        if self.symlinks == 1 { "" } else { "s" }
+      )?;
+    }
+
+    Ok(())
+  }
+}
+
+fn clone(from: &Path, to: &Path, tally: &mut Tally) -> io::Result<()> {
+  let metadata = fs::symlink_metadata(from)?;
+
+  if metadata.is_dir() {
+    fs::create_dir(to)?;
+
+    for entry in fs::read_dir(from)? {
+      let entry = entry?;
+      clone(&entry.path(), &to.join(entry.file_name()), tally)?;
+    }
+
+    return Ok(());
+  }
+
+  if metadata.is_symlink() {
+    tally.symlinks += 1;
+    return symlink(&fs::read_link(from)?, to);
+  }
+
+  if tally.method == Method::Reflink && reflink_copy::reflink(from, to).is_ok()
+  {
+    tally.reflinked += metadata.len();
+    return Ok(());
+  }
+
+  fs::hard_link(from, to)?;
+
+  tally.method = Method::Hardlink;
+
+  tally.hardlinked += metadata.len();
+
+  Ok(())
+}
+
+fn copy(from: &Path, to: &Path) -> io::Result<()> {
+  let metadata = fs::symlink_metadata(from)?;
+
+  if metadata.is_dir() {
+    fs::create_dir(to)?;
+
+    for entry in fs::read_dir(from)? {
+      let entry = entry?;
+      copy(&entry.path(), &to.join(entry.file_name()))?;
+    }
+
+    Ok(())
+  } else if metadata.is_symlink() {
+    symlink(&fs::read_link(from)?, to)
+  } else {
+    reflink_copy::reflink_or_copy(from, to).map(|_| ())
+  }
+}
+
+fn last_line(error: &Error) -> String {
+  error
+    .to_string()
+    .lines()
+    .rfind(|line| !line.trim().is_empty())
+    .unwrap_or_default()
+    .to_owned()
+}
+
+fn matches(entries: &[config::FileEntry], path: &Path) -> Result<bool> {
+  for entry in entries {
+    if entry.matches(path)? {
+      return Ok(true);
+    }
+  }
+
+  Ok(false)
+}
+
+fn size(bytes: u64) -> String {
+  const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+  if bytes < 1024 {
+    return format!("{bytes} B");
+  }
+
+  let bytes = u128::from(bytes);
+
+  let mut scale = 1024;
+
+  let mut unit = 0;
+
+  while bytes >= scale * 1024 && unit < UNITS.len() - 1 {
+    scale *= 1024;
+    unit += 1;
+  }
+
+  let tenths = bytes * 10 / scale;
+
+  format!("{}.{} {}", tenths / 10, tenths % 10, UNITS[unit])
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+  std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+  let resolved = link
+    .parent()
+    .map_or(target.into(), |parent| parent.join(target));
+
+  if resolved.is_dir() {
+    std::os::windows::fs::symlink_dir(target, link)
+  } else {
+    std::os::windows::fs::symlink_file(target, link)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sizes() {
+    assert_eq!(size(0), "0 B");
+    assert_eq!(size(1023), "1023 B");
+    assert_eq!(size(1024), "1.0 KiB");
+    assert_eq!(size(1536), "1.5 KiB");
+    assert_eq!(size(5 * 1024 * 1024), "5.0 MiB");
+    assert_eq!(size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    assert_eq!(size(2048 * 1024 * 1024 * 1024 * 1024), "2048.0 TiB");
+  }
+
+  #[test]
+  fn tally() {
+    assert_eq!(Tally::default().to_string(), "");
+
+    assert_eq!(
+      Tally {
+        hardlinked: 1024,
+        reflinked: 10,
+        symlinks: 1,
+        ..Tally::default()
+      }
+      .to_string(),
+      ", sharing 10 B via reflinks and 1.0 KiB via hardlinks, recreating 1 \
+       symlink",
+    );
+
+    assert_eq!(
+      Tally {
+        hardlinked: 10,
+        symlinks: 2,
+        ..Tally::default()
+      }
+      .to_string(),
+      ", sharing 10 B via hardlinks, recreating 2 symlinks",
+    );
+  }
+}
@@ -0,0 +1,504 @@
+use super::*;
+
+pub(crate) use checkout::Checkout;
+
+mod checkout;
+#[cfg(feature = "gix")]
+mod gitoxide;
+
+#[derive(Debug)]
+pub(crate) struct Repository {
+  backend: Backend,
+  common_dir: PathBuf,
+  toplevel: Option<PathBuf>,
+}
+
+impl Repository {
+  pub(crate) fn add_worktree(
+    &self,
+    path: &Path,
+    branch: &OsStr,
+    new_branch: bool,
+    checkout: bool,
+  ) -> Result {
+    let mut arguments = vec![OsStr::new("worktree"), OsStr::new("add")];
+
+    if !checkout {
+      arguments.push(OsStr::new("--no-checkout"));
+    }
+
+    if new_branch {
+      arguments.extend([OsStr::new("-b"), branch, path.as_os_str()]);
+    } else {
+      arguments.extend([path.as_os_str(), branch]);
+    }
+
+    git(&self.common_dir, &arguments)?;
+
+    Ok(())
+  }
+
+  pub(crate) fn at<'a>(&'a self, path: &'a Path) -> Checkout<'a> {
+    Checkout::new(path, self)
+  }
+
+  #[cfg(unix)]
+  pub(crate) fn branches(&self) -> Result<Vec<OsString>> {
+    match self.backend {
+      Backend::Git => git(
+        &self.common_dir,
+        &["for-each-ref", "--format=%(refname:short)", "refs/heads/"],
+      )
+      .map(|stdout| {
+        stdout
+          .split(|&byte| byte == b'\n')
+          .filter(|line| !line.is_empty())
+          .map(bytes::from_bytes)
+          .collect()
+      }),
+      #[cfg(feature = "gix")]
+      Backend::Gix => gitoxide::branches(&self.common_dir),
+    }
+    .context("failed to list branches")
+  }
+
+  pub(crate) fn clone_bare(url: &OsStr, path: &Path) -> Result<Self> {
+    git(
+      &env::current_dir()?,
+      &[
+        OsStr::new("clone"),
+        OsStr::new("--bare"),
+        url,
+        path.as_os_str(),
+      ],
+    )
+    .with_context(|| format!("failed to clone `{}`", url.to_string_lossy()))?;
+
+    git(
+      path,
+      &[
+        "config",
+        "remote.origin.fetch",
+        "+refs/heads/*:refs/remotes/origin/*",
+      ],
+    )
+    .context("failed to configure `remote.origin.fetch`")?;
+
+    git(path, &["fetch", "origin"]).context("failed to fetch `origin`")?;
+
+    Self::open(path)
+  }
+
+  pub(crate) fn common_dir(&self) -> &Path {
+    &self.common_dir
+  }
+
+  pub(crate) fn config(&self, key: &str) -> Option<String> {
+    let stdout = git(&self.common_dir, &["config", "--get", key]).ok()?;
+
+    Some(String::from_utf8_lossy(&stdout).trim_end().to_string())
+  }
+
+  #[cfg(unix)]
+  pub(crate) fn create_branch(&self, branch: &OsStr, start: &str) -> Result {
+    git(
+      &self.common_dir,
+      &[OsStr::new("branch"), branch, OsStr::new(start)],
+    )
+    .with_context(|| {
+      format!("failed to create branch `{}`", branch.to_string_lossy())
+    })?;
+
+    Ok(())
+  }
+
+  pub(crate) fn default_base(&self) -> Result<String> {
+    if let Some((remote, branch)) = self.remote_head() {
+      return Ok(format!("{remote}/{branch}"));
+    }
+
+    let branch = self.default_branch()?;
+
+    Ok(
+      self
+        .upstream(&branch)
+        .unwrap_or_else(|| branch.to_string_lossy().into_owned()),
+    )
+  }
+
+  pub(crate) fn default_branch(&self) -> Result<OsString> {
+    if let Some((_, branch)) = self.remote_head() {
+      return Ok(branch.into());
+    }
+
+    if self.config("core.bare").as_deref() == Some("true") {
+      let stdout = git(&self.common_dir, &["symbolic-ref", "--short", "HEAD"])
+        .context("failed to determine the default branch")?;
+
+      return Ok(bytes::from_bytes(stdout.trim_ascii_end()));
+    }
+
+    self
+      .config("init.defaultBranch")
+      .into_iter()
+      .chain(["main".into(), "master".into()])
+      .find(|branch| {
+        git(
+          &self.common_dir,
+          &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{branch}"),
+          ],
+        )
+        .is_ok()
+      })
+      .map(OsString::from)
+      .ok_or_else(|| {
+        anyhow!(
+          "failed to determine the default branch, set it with \
+           `git remote set-head origin --auto`"
+        )
+      })
+  }
+
+  #[cfg(unix)]
+  pub(crate) fn delete_branch(&self, branch: &OsStr) -> Result {
+    git(
+      &self.common_dir,
+      &[OsStr::new("branch"), OsStr::new("-D"), branch],
+    )
+    .with_context(|| {
+      format!("failed to delete branch `{}`", branch.to_string_lossy())
+    })?;
+
+    Ok(())
+  }
+
+  #[cfg(unix)]
+  pub(crate) fn delete_ref(&self, reference: &str) -> Result {
+    git(&self.common_dir, &["update-ref", "-d", reference])
+      .with_context(|| format!("failed to delete `{reference}`"))?;
+
+    Ok(())
+  }
+
+  pub(crate) fn discover() -> Result<Self> {
+    let current_dir = env::current_dir()?;
+
+    match Backend::from_env()? {
+      Backend::Git => {}
+      #[cfg(feature = "gix")]
+      Backend::Gix => {
+        if let Ok((common_dir, toplevel)) = gitoxide::discover(&current_dir) {
+          return Ok(Self {
+            backend: Backend::Gix,
+            common_dir,
+            toplevel,
+          });
+        }
+      }
+    }
+
+    let common_dir = git(
+      &current_dir,
+      &["rev-parse", "--path-format=absolute", "--git-common-dir"],
+    )
+    .context("not a git repository")?;
+
+    let toplevel = git(&current_dir, &["rev-parse", "--show-toplevel"])
+      .ok()
+      .map(|stdout| PathBuf::from(bytes::from_bytes(stdout.trim_ascii_end())));
+
+    Ok(Self {
+      backend: Backend::Git,
+      common_dir: PathBuf::from(bytes::from_bytes(common_dir.trim_ascii_end())),
+      toplevel,
+    })
+  }
+
+  pub(crate) fn find_worktree(
+    &self,
+    branch: Option<&OsStr>,
+  ) -> Result<Worktree> {
+    let worktrees = self.worktrees()?;
+
+    if let Some(branch) = branch {
+      return worktrees
+        .into_iter()
+        .find(|worktree| worktree.branch.as_deref() == Some(branch))
+        .ok_or_else(|| {
+          anyhow!(
+            "no worktree found for branch `{}`",
+            branch.to_string_lossy()
+          )
+        });
+    }
+
+    let toplevel = self
+      .toplevel
+      .as_deref()
+      .ok_or_else(|| anyhow!("not inside a worktree"))?;
+
+    worktrees
+      .into_iter()
+      .find(|worktree| !worktree.bare && worktree.path == toplevel)
+      .ok_or_else(|| anyhow!("not inside a worktree"))
+  }
+
+  pub(crate) fn lock_worktree(
+    &self,
+    path: &Path,
+    reason: Option<&str>,
+  ) -> Result {
+    let mut arguments = vec![OsStr::new("worktree"), OsStr::new("lock")];
+
+    if let Some(reason) = reason {
+      arguments.extend([OsStr::new("--reason"), OsStr::new(reason)]);
+    }
+
+    arguments.push(path.as_os_str());
+
+    git(&self.common_dir, &arguments)?;
+
+    Ok(())
+  }
+
+  pub(crate) fn main_worktree(&self) -> Result<Worktree> {
+    self
+      .worktrees()?
+      .into_iter()
+      .next()
+      .ok_or_else(|| anyhow!("no worktrees found"))
+  }
+
+  pub(crate) fn move_worktree(&self, from: &Path, to: &Path) -> Result {
+    git(
+      &self.common_dir,
+      &[
+        OsStr::new("worktree"),
+        OsStr::new("move"),
+        from.as_os_str(),
+        to.as_os_str(),
+      ],
+    )?;
+
+    Ok(())
+  }
+
+  pub(crate) fn open(common_dir: &Path) -> Result<Self> {
+    Ok(Self {
+      backend: Backend::from_env()?,
+      common_dir: common_dir.to_path_buf(),
+      toplevel: None,
+    })
+  }
+
+  pub(crate) fn prune(&self) -> Result {
+    git(&self.common_dir, &["worktree", "prune"])
+      .context("failed to prune worktrees")?;
+
+    Ok(())
+  }
+
+  pub(crate) fn register_worktree(&self, path: &Path, head: &OsStr) -> Result {
+    git(
+      &self.common_dir,
+      &[
+        OsStr::new("worktree"),
+        OsStr::new("add"),
+        OsStr::new("--no-checkout"),
+        path.as_os_str(),
+        head,
+      ],
+    )?;
+
+    Ok(())
+  }
+
+  fn remote_head(&self) -> Option<(String, String)> {
+    let stdout = git(&self.common_dir, &["remote"]).ok()?;
+
+    let remotes = String::from_utf8_lossy(&stdout).into_owned();
+
+    let (origin, others) = remotes
+      .lines()
+      .partition::<Vec<&str>, _>(|remote| *remote == "origin");
+
+    origin.into_iter().chain(others).find_map(|remote| {
+      let stdout = git(
+        &self.common_dir,
+        &["symbolic-ref", &format!("refs/remotes/{remote}/HEAD")],
+      )
+      .ok()?;
+
+      let branch = String::from_utf8_lossy(&stdout)
+        .trim_end()
+        .strip_prefix(&format!("refs/remotes/{remote}/"))?
+        .to_string();
+
+      Some((remote.to_string(), branch))
+    })
+  }
+
+  #[cfg(unix)]
+  pub(crate) fn remove_worktree(&self, path: &Path) -> Result {
+    git(
+      &self.common_dir,
+      &[
+        OsStr::new("worktree"),
+        OsStr::new("remove"),
+        OsStr::new("--force"),
+        OsStr::new("--force"),
+        path.as_os_str(),
+      ],
+    )?;
+
+    Ok(())
+  }
+
+  pub(crate) fn rename_branch(&self, old: &OsStr, new: &OsStr) -> Result {
+    git(
+      &self.common_dir,
+      &[OsStr::new("branch"), OsStr::new("-m"), old, new],
+    )
+    .with_context(|| {
+      format!(
+        "failed to rename branch `{}` to `{}`",
+        old.to_string_lossy(),
+        new.to_string_lossy()
+      )
+    })?;
+
+    Ok(())
+  }
+
+  pub(crate) fn repair_worktrees(&self, paths: &[PathBuf]) -> Result {
+    let mut arguments = vec![OsStr::new("worktree"), OsStr::new("repair")];
+
+    arguments.extend(paths.iter().map(|path| path.as_os_str()));
+
+    git(&self.common_dir, &arguments).context("failed to repair worktrees")?;
+
+    Ok(())
+  }
+
+  pub(crate) fn set_config(&self, key: &str, value: &str) -> Result {
+    git(&self.common_dir, &["config", key, value])
+      .with_context(|| format!("failed to set `{key}`"))?;
+
+    Ok(())
+  }
+
+  pub(crate) fn set_upstream(&self, branch: &OsStr, remote: &str) -> Result {
+    let mut upstream = OsString::from(format!("--set-upstream-to={remote}/"));
+
+    upstream.push(branch);
+
+    git(&self.common_dir, &[OsStr::new("branch"), &upstream, branch])
+      .with_context(|| {
+        format!(
+          "failed to set upstream of `{}` to `{remote}`",
+          branch.to_string_lossy()
+        )
+      })?;
+
+    Ok(())
+  }
+
+  pub(crate) fn unlock_worktree(&self, path: &Path) -> Result {
+    git(
+      &self.common_dir,
+      &[
+        OsStr::new("worktree"),
+        OsStr::new("unlock"),
+        path.as_os_str(),
+      ],
+    )?;
+
+    Ok(())
+  }
+
+  #[cfg(unix)]
+  pub(crate) fn update_ref(&self, reference: &str, target: &str) -> Result {
+    git(&self.common_dir, &["update-ref", reference, target])
+      .with_context(|| format!("failed to update `{reference}`"))?;
+
+    Ok(())
+  }
+
+  pub(crate) fn upstream(&self, branch: &OsStr) -> Option<String> {
+    let mut revision = branch.to_os_string();
+
+    revision.push("@{upstream}");
+
+    let stdout = git(
+      &self.common_dir,
+      &[
+        OsStr::new("rev-parse"),
+        OsStr::new("--abbrev-ref"),
+        &revision,
+      ],
+    )
+    .ok()?;
+
+    Some(String::from_utf8_lossy(&stdout).trim_end().to_string())
+  }
+
+  pub(crate) fn worktree_path(&self, branch: &OsStr) -> Result<PathBuf> {
+    let main = self.main_worktree()?;
+
+    let parent = main.path.parent().ok_or_else(|| {
+      anyhow!(
+        "repo root `{}` has no parent directory",
+        main.path.display()
+      )
+    })?;
+
+    if main.bare {
+      return Ok(parent.join(Worktree::branch_directory(branch)));
+    }
+
+    let project = main.path.file_name().ok_or_else(|| {
+      anyhow!("failed to get project name from `{}`", main.path.display())
+    })?;
+
+    Ok(parent.join(Worktree::directory_name(project, branch)))
+  }
+
+  pub(crate) fn worktrees(&self) -> Result<Vec<Worktree>> {
+    match self.backend {
+      Backend::Git => {
+        git(&self.common_dir, &["worktree", "list", "--porcelain", "-z"])
+          .map(|stdout| Worktree::parse(&stdout))
+      }
+      #[cfg(feature = "gix")]
+      Backend::Gix => gitoxide::worktrees(&self.common_dir),
+    }
+    .context("failed to list worktrees")
+  }
+}
+
+fn git(directory: &Path, arguments: &[impl AsRef<OsStr>]) -> Result<Vec<u8>> {
+  let output = Command::new("git")
+    .args(arguments)
+    .current_dir(directory)
+    .stdin(Stdio::null())
+    .output()
+    .context("failed to run `git`")?;
+
+  if !output.status.success() {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    let stderr = stderr.trim();
+
+    if stderr.is_empty() {
+      bail!("`git` failed with {}", output.status);
+    }
+
+    bail!("{stderr}");
+  }
+
+  Ok(output.stdout)
+}
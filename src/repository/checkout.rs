@@ -0,0 +1,293 @@
+use super::*;
+
+pub(crate) struct Checkout<'a> {
+  path: &'a Path,
+  repository: &'a Repository,
+}
+
+impl<'a> Checkout<'a> {
+  pub(crate) fn ahead(&self, base: &str) -> Result<usize> {
+    Ok(
+      str::from_utf8(&self.git(&[
+        "rev-list",
+        "--count",
+        &format!("{base}..HEAD"),
+      ])?)?
+      .trim()
+      .parse()?,
+    )
+  }
+
+  pub(crate) fn behind(&self, base: &str) -> Result<usize> {
+    Ok(
+      str::from_utf8(&self.git(&[
+        "rev-list",
+        "--count",
+        &format!("HEAD..{base}"),
+      ])?)?
+      .trim()
+      .parse()?,
+    )
+  }
+
+  pub(crate) fn changes(&self) -> Result<(usize, usize, usize)> {
+    let stdout = self.git(&["status", "--porcelain", "-z"])?;
+
+    let mut entries = stdout.split(|&byte| byte == 0);
+
+    let (mut staged, mut unstaged, mut untracked) = (0, 0, 0);
+
+    while let Some(entry) = entries.next() {
+      let [index, worktree, ..] = *entry else {
+        continue;
+      };
+
+      if (index, worktree) == (b'?', b'?') {
+        untracked += 1;
+        continue;
+      }
+
+      staged += usize::from(index != b' ');
+      unstaged += usize::from(worktree != b' ');
+
+      if matches!(index, b'R' | b'C') {
+        entries.next();
+      }
+    }
+
+    Ok((staged, unstaged, untracked))
+  }
+
+  pub(crate) fn checkout(&self) -> Result {
+    self.git(&["checkout"])?;
+
+    Ok(())
+  }
+
+  pub(crate) fn conflicts(&self) -> Result<usize> {
+    Ok(
+      self
+        .git(&["diff", "--name-only", "-z", "--diff-filter=U"])?
+        .split(|&byte| byte == 0)
+        .filter(|file| !file.is_empty())
+        .count(),
+    )
+  }
+
+  pub(crate) fn diff_stat(&self) -> Result<(usize, usize)> {
+    match self.repository.backend {
+      Backend::Git => Ok(
+        String::from_utf8_lossy(&self.git(&["diff", "--numstat"])?)
+          .lines()
+          .fold((0, 0), |(insertions, deletions), line| {
+            let mut parts = line.split('\t');
+
+            let added = parts
+              .next()
+              .and_then(|part| part.parse::<usize>().ok())
+              .unwrap_or(0);
+
+            let removed = parts
+              .next()
+              .and_then(|part| part.parse::<usize>().ok())
+              .unwrap_or(0);
+
+            (insertions + added, deletions + removed)
+          }),
+      ),
+      #[cfg(feature = "gix")]
+      Backend::Gix => gitoxide::diff_stat(self.path),
+    }
+  }
+
+  pub(crate) fn dirty(&self) -> Result<bool> {
+    Ok(
+      !self
+        .git(&["status", "--porcelain", "--untracked-files=no"])?
+        .is_empty(),
+    )
+  }
+
+  pub(crate) fn files(&self) -> Result<Vec<PathBuf>> {
+    Ok(
+      self
+        .git(&[
+          "ls-files",
+          "-z",
+          "--cached",
+          "--others",
+          "--exclude-standard",
+        ])?
+        .split(|&byte| byte == 0)
+        .filter(|file| !file.is_empty())
+        .map(|file| self.path.join(bytes::from_bytes(file)))
+        .collect(),
+    )
+  }
+
+  fn git(&self, arguments: &[impl AsRef<OsStr>]) -> Result<Vec<u8>> {
+    git(self.path, arguments)
+  }
+
+  pub(crate) fn ignored(&self) -> Result<Vec<PathBuf>> {
+    Ok(
+      self
+        .git(&[
+          "ls-files",
+          "-z",
+          "--others",
+          "--ignored",
+          "--exclude-standard",
+          "--directory",
+        ])?
+        .split(|&byte| byte == 0)
+        .filter(|file| !file.is_empty())
+        .map(|file| {
+          bytes::from_bytes(file.strip_suffix(b"/").unwrap_or(file)).into()
+        })
+        .collect(),
+    )
+  }
+
+  pub(crate) fn last_commit(&self) -> Result<(u64, String)> {
+    match self.repository.backend {
+      Backend::Git => {
+        let stdout = self.git(&["log", "-1", "--format=%ct%x00%s"])?;
+
+        let stdout = String::from_utf8_lossy(&stdout);
+
+        let (time, subject) = stdout
+          .trim_end()
+          .split_once('\0')
+          .ok_or_else(|| anyhow!("unexpected `git log` output"))?;
+
+        Ok((time.parse()?, subject.to_string()))
+      }
+      #[cfg(feature = "gix")]
+      Backend::Gix => gitoxide::last_commit(self.path),
+    }
+  }
+
+  pub(crate) fn lfs_pull(&self) -> Result {
+    self.git(&["lfs", "pull"])?;
+
+    Ok(())
+  }
+
+  pub(crate) fn lfs_tracked(&self) -> Result<bool> {
+    Ok(
+      !self
+        .git(&["ls-files", "-z", "--", ":(attr:filter=lfs)"])?
+        .is_empty(),
+    )
+  }
+
+  pub(crate) fn merge(&self, base: &str) -> Result {
+    self.git(&["merge", "--no-edit", base])?;
+
+    Ok(())
+  }
+
+  pub(crate) fn new(path: &'a Path, repository: &'a Repository) -> Self {
+    Self { path, repository }
+  }
+
+  pub(crate) fn rebase(&self, base: &str) -> Result {
+    self.git(&["rebase", base])?;
+
+    Ok(())
+  }
+
+  pub(crate) fn resolve(&self, revision: &str) -> Result<String> {
+    let stdout = self.git(&[
+      "rev-parse",
+      "--verify",
+      "--quiet",
+      &format!("{revision}^{{commit}}"),
+    ])?;
+
+    Ok(String::from_utf8_lossy(&stdout).trim_end().to_string())
+  }
+
+  pub(crate) fn sparse_checkout(
+    &self,
+    profile: &str,
+    directories: &[String],
+  ) -> Result {
+    self.git(&["sparse-checkout", "init", "--cone"])?;
+
+    self.git(
+      &["sparse-checkout", "set", "--"]
+        .into_iter()
+        .chain(directories.iter().map(String::as_str))
+        .collect::<Vec<_>>(),
+    )?;
+
+    self.git(&["config", "--worktree", "wt.sparse", profile])?;
+
+    Ok(())
+  }
+
+  pub(crate) fn sparse_profile(&self) -> Option<String> {
+    let stdout = self.git(&["config", "--get", "wt.sparse"]).ok()?;
+
+    Some(String::from_utf8_lossy(&stdout).trim_end().to_string())
+  }
+
+  pub(crate) fn submodules(&self) -> Vec<(String, PathBuf)> {
+    let Ok(stdout) = self.git(&[
+      "config",
+      "-z",
+      "--file",
+      ".gitmodules",
+      "--get-regexp",
+      r"^submodule\..*\.path$",
+    ]) else {
+      return Vec::new();
+    };
+
+    stdout
+      .split(|&byte| byte == 0)
+      .filter_map(|record| {
+        let newline = record.iter().position(|&byte| byte == b'\n')?;
+
+        let name = str::from_utf8(&record[..newline])
+          .ok()?
+          .strip_prefix("submodule.")?
+          .strip_suffix(".path")?;
+
+        Some((
+          name.to_owned(),
+          bytes::from_bytes(&record[newline + 1..]).into(),
+        ))
+      })
+      .collect()
+  }
+
+  pub(crate) fn update_submodule(
+    &self,
+    submodule: &Path,
+    reference: Option<&Path>,
+  ) -> Result {
+    let mut arguments = vec![
+      OsStr::new("submodule"),
+      OsStr::new("update"),
+      OsStr::new("--init"),
+      OsStr::new("--recursive"),
+    ];
+
+    if let Some(reference) = reference {
+      arguments.extend([
+        OsStr::new("--reference"),
+        reference.as_os_str(),
+        OsStr::new("--dissociate"),
+      ]);
+    }
+
+    arguments.extend([OsStr::new("--"), submodule.as_os_str()]);
+
+    self.git(&arguments)?;
+
+    Ok(())
+  }
+}
@@ -0,0 +1,174 @@
+use {
+  super::*,
+  gix::{
+    bstr::ByteSlice,
+    diff::blob::{Algorithm, InternedInput, diff_with_slider_heuristics},
+    progress::Discard,
+    status::{UntrackedFiles, index_worktree::Item},
+  },
+};
+
+#[cfg(unix)]
+pub(crate) fn branches(common_dir: &Path) -> Result<Vec<OsString>> {
+  let repository = gix::open(common_dir)?;
+
+  let mut branches = repository
+    .references()?
+    .local_branches()?
+    .map(|reference| Ok(bytes::from_bytes(reference?.name().shorten())))
+    .collect::<Result<Vec<_>>>()?;
+
+  branches.sort();
+
+  Ok(branches)
+}
+
+fn describe(repository: &gix::Repository) -> Result<Worktree> {
+  let Some(workdir) = repository.workdir() else {
+    return Ok(Worktree {
+      bare: true,
+      path: gix::path::realpath(repository.git_dir())?,
+      ..Worktree::default()
+    });
+  };
+
+  let head = repository.head()?;
+
+  let branch = head.referent_name().map(|name| {
+    let name = name.as_bstr().as_bytes();
+    bytes::from_bytes(name.strip_prefix(b"refs/heads/").unwrap_or(name))
+  });
+
+  Ok(Worktree {
+    branch,
+    detached: head.is_detached(),
+    head: Some(head.id().map_or_else(
+      || gix::ObjectId::null(repository.object_hash()).to_string(),
+      |id| id.to_string(),
+    )),
+    path: gix::path::realpath(workdir)
+      .unwrap_or_else(|_| workdir.to_path_buf()),
+    ..Worktree::default()
+  })
+}
+
+pub(crate) fn diff_stat(path: &Path) -> Result<(usize, usize)> {
+  let repository = gix::open(path)?;
+
+  let workdir = repository
+    .workdir()
+    .ok_or_else(|| anyhow!("`{}` has no working tree", path.display()))?;
+
+  let mut stat = (0, 0);
+
+  for item in repository
+    .status(Discard)?
+    .untracked_files(UntrackedFiles::None)
+    .index_worktree_submodules(None)
+    .into_index_worktree_iter(Vec::new())?
+  {
+    let Item::Modification {
+      entry, rela_path, ..
+    } = item?
+    else {
+      continue;
+    };
+
+    let old = repository.find_blob(entry.id)?.data.clone();
+
+    let new =
+      fs::read(workdir.join(bytes::from_bytes(&rela_path))).unwrap_or_default();
+
+    let (insertions, deletions) = line_changes(&old, &new);
+
+    stat = (stat.0 + insertions, stat.1 + deletions);
+  }
+
+  Ok(stat)
+}
+
+pub(crate) fn discover(directory: &Path) -> Result<(PathBuf, Option<PathBuf>)> {
+  let repository = gix::discover_with_environment_overrides(directory)?;
+
+  Ok((
+    gix::path::realpath(repository.common_dir())?,
+    repository.workdir().map(gix::path::realpath).transpose()?,
+  ))
+}
+
+pub(crate) fn last_commit(path: &Path) -> Result<(u64, String)> {
+  let repository = gix::open(path)?;
+
+  let commit = repository.head_commit()?;
+
+  Ok((
+    commit.time()?.seconds.try_into()?,
+    commit.message()?.summary().to_str_lossy().into_owned(),
+  ))
+}
+
+fn line_changes(old: &[u8], new: &[u8]) -> (usize, usize) {
+  if old.contains(&0) || new.contains(&0) {
+    return (0, 0);
+  }
+
+  let diff = diff_with_slider_heuristics(
+    Algorithm::Histogram,
+    &InternedInput::new(old, new),
+  );
+
+  (
+    diff.count_additions() as usize,
+    diff.count_removals() as usize,
+  )
+}
+
+fn linked(proxy: gix::worktree::Proxy<'_>) -> Worktree {
+  let path = proxy
+    .base()
+    .unwrap_or_else(|_| proxy.git_dir().to_path_buf());
+
+  let locked = proxy
+    .lock_reason()
+    .map(|reason| reason.to_str_lossy().trim_end().to_string());
+
+  let prunable = if locked.is_some() {
+    None
+  } else {
+    prune_reason(proxy.git_dir())
+  };
+
+  let mut worktree = proxy
+    .into_repo_with_possibly_inaccessible_worktree()
+    .map_or_else(
+      |_| Worktree::default(),
+      |repository| describe(&repository).unwrap_or_default(),
+    );
+
+  worktree.locked = locked;
+  worktree.path = path;
+  worktree.prunable = prunable;
+
+  worktree
+}
+
+fn prune_reason(admin: &Path) -> Option<String> {
+  let Ok(contents) = fs::read(admin.join("gitdir")) else {
+    return Some("gitdir file does not exist".into());
+  };
+
+  (!admin
+    .join(bytes::from_bytes(contents.trim_ascii_end()))
+    .exists())
+  .then(|| "gitdir file points to non-existent location".into())
+}
+
+pub(crate) fn worktrees(common_dir: &Path) -> Result<Vec<Worktree>> {
+  let repository = gix::open(common_dir)?;
+
+  let mut worktrees = vec![describe(&repository.main_repo()?)?];
+
+  worktrees.extend(repository.worktrees()?.into_iter().map(linked));
+
+  Ok(worktrees)
+}
@@ -0,0 +1,13 @@
+use super::*;
+
+#[derive(
+  Clone, Copy, Debug, Default, Deserialize, Serialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Strategy {
+  /// Merge the base into each branch.
+  Merge,
+  /// Rebase each branch onto the base.
+  #[default]
+  Rebase,
+}
@@ -4,6 +4,7 @@ pub(crate) const BOLD: &str = "1";
 pub(crate) const CYAN: &str = "36";
 pub(crate) const GREEN: &str = "32";
 pub(crate) const RED: &str = "31";
+pub(crate) const YELLOW: &str = "33";
 
 pub(crate) struct Styled<T> {
   code: &'static str,
@@ -69,7 +70,6 @@ impl Style {
     Self { enabled }
   }
 
-  #[cfg(unix)]
   pub(crate) fn stderr() -> Self {
     Self::new(io::stderr().is_terminal())
   }
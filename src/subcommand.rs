@@ -1,49 +1,119 @@
-use {super::*, create::Create, hook::Hook, init::Init};
+use {
+  super::*, create::Create, doctor::Doctor, exec::Exec, hook::Hook, init::Init,
+  list::List, lock::Lock, remove::Remove, restore::Restore, unlock::Unlock,
+};
 
+mod clone;
+mod config;
 mod convert;
+mod convert_repo;
 mod create;
+mod delete;
+mod doctor;
+mod exec;
+mod gc;
 mod hook;
+mod hooks;
 mod init;
 mod list;
+mod lock;
+mod r#move;
 mod remove;
+mod restore;
+mod sparse;
+mod status;
 mod switch;
+mod sync;
+mod unlock;
 
 #[derive(Debug, Parser)]
 pub(crate) enum Subcommand {
+  /// Clone a repository into a bare worktree layout.
+  Clone(clone::Clone),
+  /// Get, set, or edit configuration.
+  #[clap(subcommand)]
+  Config(config::Config),
   /// Convert existing branches to worktrees.
   #[clap(alias = "cv")]
   Convert,
+  /// Convert the repository into a bare worktree layout.
+  ConvertRepo,
   /// Create a new worktree.
   #[clap(alias = "c")]
   Create(Create),
   #[clap(hide = true)]
+  Delete(delete::Delete),
+  /// Diagnose broken worktree state.
+  Doctor(Doctor),
+  /// Run a command in every worktree.
+  #[clap(alias = "x")]
+  Exec(Exec),
+  /// Finish interrupted removals and purge expired trash.
+  Gc,
+  #[clap(hide = true)]
   Hook(Hook),
+  /// Inspect configured hooks.
+  #[clap(subcommand)]
+  Hooks(hooks::Hooks),
   /// Generate shell integration.
   Init(Init),
   /// List all worktrees.
   #[clap(alias = "l")]
-  List,
+  List(List),
+  /// Lock a worktree so it can't be removed.
+  Lock(Lock),
+  /// Rename a worktree's branch and directory.
+  #[clap(alias = "mv")]
+  Move(r#move::Move),
   /// Remove worktrees.
   #[clap(alias = "r")]
-  Remove,
+  Remove(Remove),
+  /// Fix broken worktree state found by `doctor`.
+  Repair,
+  /// Restore a removed worktree from the trash.
+  Restore(Restore),
+  /// Change the sparse-checkout profile of the current worktree.
+  Sparse(sparse::Sparse),
+  /// Show the state of the current worktree.
+  Status,
   /// Switch to a different worktree.
   #[clap(alias = "s")]
   Switch,
+  /// Rebase or merge every worktree branch onto the default branch.
+  Sync(sync::Sync),
+  /// Unlock a worktree.
+  Unlock(Unlock),
 }
 
 impl Subcommand {
   pub(crate) fn run(self) -> Result {
     match self {
+      Self::Clone(clone) => clone.run(),
+      Self::Config(config) => config.run(),
       Self::Convert => convert::run(),
+      Self::ConvertRepo => convert_repo::run(),
       Self::Create(create) => create.run(),
+      Self::Delete(delete) => delete.run(),
+      Self::Doctor(doctor) => doctor.run(),
+      Self::Exec(exec) => exec.run(),
+      Self::Gc => gc::run(),
       Self::Hook(hook) => hook.run(),
+      Self::Hooks(hooks) => hooks.run(),
       Self::Init(init) => {
         init.run();
         Ok(())
       }
-      Self::List => list::run(),
-      Self::Remove => remove::run(),
+      Self::List(list) => list.run(),
+      Self::Lock(lock) => lock.run(),
+      Self::Move(r#move) => r#move.run(),
+      Self::Remove(remove) => remove.run(),
+      Self::Repair => doctor::repair(),
+      Self::Restore(restore) => restore.run(),
+      Self::Sparse(sparse) => sparse.run(),
+      Self::Status => status::run(),
       Self::Switch => switch::run(),
+      Self::Sync(sync) => sync.run(),
+      Self::Unlock(unlock) => unlock.run(),
     }
   }
 }
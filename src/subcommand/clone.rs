@@ -0,0 +1,118 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Clone {
+  /// Directory to clone into, defaults to the repository name.
+  #[clap(index = 2)]
+  directory: Option<PathBuf>,
+  /// Repository to clone.
+  #[clap(index = 1)]
+  url: OsString,
+}
+
+impl Clone {
+  pub(crate) fn run(self) -> Result {
+    let style = Style::stderr();
+
+    let directory = env::current_dir()?.join(match self.directory {
+      Some(directory) => directory,
+      None => infer_directory(&self.url)?,
+    });
+
+    if fs::read_dir(&directory)
+      .is_ok_and(|mut entries| entries.next().is_some())
+    {
+      bail!(
+        "destination `{}` already exists and is not empty",
+        directory.display()
+      );
+    }
+
+    let repository =
+      Repository::clone_bare(&self.url, &directory.join(".bare"))?;
+
+    fs::write(directory.join(".git"), "gitdir: ./.bare\n")
+      .context("failed to write `.git` file")?;
+
+    eprintln!(
+      "{} {} into {}",
+      style.apply(style::GREEN, "cloned"),
+      style.apply(style::BOLD, self.url.to_string_lossy()),
+      style.apply(style::CYAN, directory.display()),
+    );
+
+    let branch = repository.default_branch()?;
+
+    let worktree = directory.join(Worktree::branch_directory(&branch));
+
+    repository
+      .add_worktree(&worktree, &branch, false, true)
+      .with_context(|| {
+        format!("failed to create worktree `{}`", branch.to_string_lossy())
+      })?;
+
+    repository.set_upstream(&branch, "origin")?;
+
+    eprintln!(
+      "{} worktree {} at {}",
+      style.apply(style::GREEN, "created"),
+      style.apply(style::BOLD, branch.to_string_lossy()),
+      style.apply(style::CYAN, worktree.display()),
+    );
+
+    bytes::print_path(&worktree)?;
+
+    Ok(())
+  }
+}
+
+fn infer_directory(url: &OsStr) -> Result<PathBuf> {
+  let url = url.to_string_lossy();
+
+  let name = url
+    .trim_end_matches('/')
+    .rsplit(['/', ':', '\\'])
+    .next()
+    .unwrap_or_default();
+
+  let name = name.strip_suffix(".git").unwrap_or(name);
+
+  if name.is_empty() {
+    bail!("failed to infer a directory name from `{url}`");
+  }
+
+  Ok(PathBuf::from(name))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn infer() {
+    #[track_caller]
+    fn case(url: &str, expected: &str) {
+      assert_eq!(
+        infer_directory(OsStr::new(url)).unwrap(),
+        PathBuf::from(expected)
+      );
+    }
+
+    case("https://github.com/terror/wt", "wt");
+    case("https://github.com/terror/wt.git", "wt");
+    case("https://github.com/terror/wt/", "wt");
+    case("git@github.com:terror/wt.git", "wt");
+    case("host:wt.git", "wt");
+    case("file:///tmp/project", "project");
+  }
+
+  #[test]
+  fn infer_empty() {
+    assert_eq!(
+      infer_directory(OsStr::new("https://host/.git"))
+        .unwrap_err()
+        .to_string(),
+      "failed to infer a directory name from `https://host/.git`",
+    );
+  }
+}
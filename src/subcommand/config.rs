@@ -0,0 +1,224 @@
+use {
+  super::*,
+  toml_edit::{DocumentMut, Item, Table},
+};
+
+#[derive(Debug, Parser)]
+pub(crate) enum Config {
+  /// Open the config file in `$EDITOR` and save it if it's valid.
+  Edit {
+    /// Edit the repo-local config instead of the user config.
+    #[clap(long)]
+    repo: bool,
+  },
+  /// Print the value of a config key, e.g. `trash.enabled`.
+  Get {
+    /// Dotted key to print.
+    key: String,
+  },
+  /// Print the path of the config file.
+  Path {
+    /// Print the path of the repo-local config instead of the user config.
+    #[clap(long)]
+    repo: bool,
+  },
+  /// Set a config key, keeping existing comments and formatting.
+  Set {
+    /// Dotted key to set.
+    #[clap(index = 1)]
+    key: String,
+    /// Write to the repo-local config instead of the user config.
+    #[clap(long)]
+    repo: bool,
+    /// Value to set, parsed as TOML or taken as a string.
+    #[clap(index = 2)]
+    value: String,
+  },
+  /// Print the effective config, including defaults.
+  Show,
+}
+
+impl Config {
+  fn edit(path: &Path) -> Result {
+    let contents = crate::Config::contents(path)?;
+
+    let mut temporary = tempfile::Builder::new()
+      .prefix("wt-config-")
+      .suffix(".toml")
+      .tempfile()
+      .context("failed to create temporary config file")?;
+
+    temporary
+      .write_all(contents.as_bytes())
+      .and_then(|()| temporary.flush())
+      .with_context(|| {
+        format!("failed to write `{}`", temporary.path().display())
+      })?;
+
+    let editor = env::var("EDITOR")
+      .ok()
+      .filter(|editor| !editor.trim().is_empty())
+      .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.into());
+
+    let mut words = editor.split_whitespace();
+
+    let program = words.next().unwrap_or_default();
+
+    let status = Command::new(program)
+      .args(words)
+      .arg(temporary.path())
+      .status()
+      .with_context(|| format!("failed to run editor `{editor}`"))?;
+
+    if !status.success() {
+      bail!("editor `{editor}` failed with {status}");
+    }
+
+    let edited = fs::read_to_string(temporary.path()).with_context(|| {
+      format!("failed to read `{}`", temporary.path().display())
+    })?;
+
+    let style = Style::stderr();
+
+    if edited == contents {
+      eprintln!("config unchanged");
+      return Ok(());
+    }
+
+    if let Err(error) = crate::Config::parse(temporary.path(), &edited) {
+      let (_, kept) = temporary.keep()?;
+
+      return Err(error).with_context(|| {
+        format!("config not saved, your changes are in `{}`", kept.display())
+      });
+    }
+
+    Self::write(path, &edited)?;
+
+    eprintln!(
+      "{} {}",
+      style.apply(style::GREEN, "saved"),
+      style.apply(style::CYAN, path.display()),
+    );
+
+    Ok(())
+  }
+
+  fn get(key: &str) -> Result {
+    let toml::Value::Table(table) =
+      toml::Value::try_from(crate::Config::load()?)?
+    else {
+      bail!("failed to serialize config");
+    };
+
+    let Some(value) = crate::Config::lookup(&table, key) else {
+      bail!("config key `{key}` is not set");
+    };
+
+    match value {
+      toml::Value::String(string) => println!("{string}"),
+      toml::Value::Table(table) => print!("{}", toml::to_string(table)?),
+      value => println!("{value}"),
+    }
+
+    Ok(())
+  }
+
+  fn path(repo: bool) -> Result<PathBuf> {
+    if repo {
+      crate::Config::repo_path()
+    } else {
+      crate::Config::path()
+    }
+  }
+
+  pub(crate) fn run(self) -> Result {
+    match self {
+      Self::Edit { repo } => Self::edit(&Self::path(repo)?),
+      Self::Get { key } => Self::get(&key),
+      Self::Path { repo } => {
+        println!("{}", Self::path(repo)?.display());
+        Ok(())
+      }
+      Self::Set { key, repo, value } => {
+        Self::set(&Self::path(repo)?, &key, &value)
+      }
+      Self::Show => {
+        print!("{}", toml::to_string(&crate::Config::load()?)?);
+        Ok(())
+      }
+    }
+  }
+
+  fn set(path: &Path, key: &str, value: &str) -> Result {
+    let contents = crate::Config::contents(path)?;
+
+    crate::Config::parse(path, &contents)?;
+
+    let mut document = contents.parse::<DocumentMut>()?;
+
+    let segments = key.split('.').collect::<Vec<&str>>();
+
+    let Some((last, parents)) = segments
+      .split_last()
+      .filter(|_| segments.iter().all(|segment| !segment.is_empty()))
+    else {
+      bail!("invalid config key `{key}`");
+    };
+
+    let mut table = document.as_table_mut() as &mut dyn toml_edit::TableLike;
+
+    for (i, parent) in parents.iter().enumerate() {
+      table = table
+        .entry(parent)
+        .or_insert_with(|| {
+          let mut table = Table::new();
+          table.set_implicit(true);
+          Item::Table(table)
+        })
+        .as_table_like_mut()
+        .ok_or_else(|| {
+          anyhow!("config key `{}` is not a table", parents[..=i].join("."))
+        })?;
+    }
+
+    let mut new = value
+      .parse::<toml_edit::Value>()
+      .unwrap_or_else(|_| value.into());
+
+    if let Some(existing) = table.get_mut(last).and_then(Item::as_value_mut) {
+      *new.decor_mut() = existing.decor().clone();
+      *existing = new;
+    } else {
+      new.decor_mut().clear();
+      table.insert(last, Item::Value(new));
+    }
+
+    let updated = document.to_string();
+
+    crate::Config::parse(path, &updated)
+      .with_context(|| format!("failed to set `{key}`"))?;
+
+    Self::write(path, &updated)?;
+
+    let style = Style::stderr();
+
+    eprintln!(
+      "{} {} in {}",
+      style.apply(style::GREEN, "set"),
+      style.apply(style::BOLD, key),
+      style.apply(style::CYAN, path.display()),
+    );
+
+    Ok(())
+  }
+
+  fn write(path: &Path, contents: &str) -> Result {
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, contents)
+      .with_context(|| format!("failed to write `{}`", path.display()))
+  }
+}
@@ -1,16 +1,16 @@
 use super::*;
 
 #[cfg(unix)]
-struct Branch(String);
+struct Branch(OsString);
 
 #[cfg(unix)]
 impl SkimItem for Branch {
   fn output(&self) -> Cow<'_, str> {
-    Cow::Borrowed(&self.0)
+    self.0.to_string_lossy()
   }
 
   fn text(&self) -> Cow<'_, str> {
-    Cow::Borrowed(&self.0)
+    self.0.to_string_lossy()
   }
 }
 
@@ -21,55 +21,21 @@ pub(crate) fn run() -> Result {
 
 #[cfg(unix)]
 pub(crate) fn run() -> Result {
-  let style = Style::stderr();
-
-  let root = Command::new("git")
-    .args(["rev-parse", "--show-toplevel"])
-    .stderr(Stdio::null())
-    .output()?;
-
-  if !root.status.success() {
-    bail!("not a git repository");
-  }
+  let config = Config::load()?;
 
-  let root = Path::new(str::from_utf8(&root.stdout)?.trim()).to_path_buf();
-
-  let project = root
-    .file_name()
-    .ok_or_else(|| {
-      anyhow!("failed to get project name from `{}`", root.display())
-    })?
-    .to_string_lossy()
-    .to_string();
-
-  let branch_output = Command::new("git")
-    .args(["branch", "--format=%(refname:short)"])
-    .stderr(Stdio::null())
-    .output()?;
-
-  if !branch_output.status.success() {
-    bail!("failed to list branches");
-  }
+  let style = Style::stderr();
 
-  let all_branches = str::from_utf8(&branch_output.stdout)?
-    .lines()
-    .map(str::to_string)
-    .collect::<Vec<_>>();
+  let repository = Repository::discover()?;
 
-  let worktree_output = Command::new("git")
-    .args(["worktree", "list", "--porcelain"])
-    .stderr(Stdio::null())
-    .output()?;
+  let provision = Provision::new(&config.create, &repository, style);
 
-  if !worktree_output.status.success() {
-    bail!("failed to list worktrees");
-  }
+  let worktree_branches = repository
+    .worktrees()?
+    .into_iter()
+    .filter_map(|worktree| worktree.branch)
+    .collect::<HashSet<_>>();
 
-  let worktree_branches = str::from_utf8(&worktree_output.stdout)?
-    .split("\n\n")
-    .filter_map(|block| Worktree::try_from(block).ok())
-    .map(|w| w.branch)
-    .collect::<std::collections::HashSet<_>>();
+  let all_branches = repository.branches()?;
 
   let branches = all_branches
     .into_iter()
@@ -103,50 +69,45 @@ pub(crate) fn run() -> Result {
   let selected = output
     .selected_items
     .iter()
-    .map(|item| item.text().to_string())
+    .filter_map(|item| item.item.as_ref().as_any().downcast_ref::<Branch>())
+    .map(|branch| branch.0.clone())
     .collect::<Vec<_>>();
 
   if selected.is_empty() {
     return Ok(());
   }
 
-  let parent = root.parent().ok_or_else(|| {
-    anyhow!("repo root `{}` has no parent directory", root.display())
-  })?;
-
   let mut created = Vec::new();
 
   for branch in &selected {
-    let dir_name = format!("{}.{}", project, branch.replace('/', "-"));
-
-    let worktree = parent.join(&dir_name);
+    let worktree = repository.worktree_path(branch)?;
 
-    let result = Command::new("git")
-      .args(["worktree", "add", &worktree.to_string_lossy(), branch])
-      .stdout(Stdio::null())
-      .stderr(Stdio::piped())
-      .output()?;
-
-    if !result.status.success() {
-      bail!(
-        "failed to create worktree for `{}`: {}",
-        branch,
-        str::from_utf8(&result.stderr)?.trim()
-      );
-    }
+    repository
+      .add_worktree(&worktree, branch, false, true)
+      .with_context(|| {
+        format!(
+          "failed to create worktree for `{}`",
+          branch.to_string_lossy()
+        )
+      })?;
 
     eprintln!(
       "{} worktree {} at {}",
       style.apply(style::GREEN, "created"),
-      style.apply(style::BOLD, branch),
-      style.apply(style::CYAN, &dir_name),
+      style.apply(style::BOLD, branch.to_string_lossy()),
+      style.apply(
+        style::CYAN,
+        worktree.file_name().unwrap_or_default().to_string_lossy()
+      ),
     );
 
+    provision.apply(&worktree)?;
+
     created.push(worktree);
   }
 
   if selected.len() == 1 {
-    println!("{}", created[0].display());
+    bytes::print_path(&created[0])?;
   }
 
   Ok(())
@@ -0,0 +1,210 @@
+use super::*;
+
+const IN_PROGRESS: &[(&str, &str)] = &[
+  ("BISECT_LOG", "a bisect"),
+  ("CHERRY_PICK_HEAD", "a cherry-pick"),
+  ("MERGE_HEAD", "a merge"),
+  ("REVERT_HEAD", "a revert"),
+  ("rebase-apply", "a rebase"),
+  ("rebase-merge", "a rebase"),
+];
+
+fn check(git_dir: &Path) -> Result {
+  if !git_dir.is_dir() {
+    bail!("`{}` is not a directory", git_dir.display());
+  }
+
+  for (file, operation) in IN_PROGRESS {
+    if git_dir.join(file).exists() {
+      bail!("refusing to convert while {operation} is in progress");
+    }
+  }
+
+  if git_dir.join("modules").exists() {
+    bail!("refusing to convert a repository with submodules");
+  }
+
+  Ok(())
+}
+
+fn convert(
+  root: &Path,
+  staging: &Path,
+  worktree: &Path,
+  head: &OsStr,
+) -> Result {
+  let git_dir = root.join(".git");
+
+  for entry in fs::read_dir(root)? {
+    let path = entry?.path();
+
+    if path != git_dir && path != staging {
+      fs::rename(&path, staging.join(path.file_name().unwrap_or_default()))
+        .with_context(|| format!("failed to move `{}`", path.display()))?;
+    }
+  }
+
+  let bare = root.join(".bare");
+
+  fs::rename(&git_dir, &bare).context("failed to move `.git` to `.bare`")?;
+
+  fs::write(&git_dir, "gitdir: ./.bare\n")
+    .context("failed to write `.git` file")?;
+
+  let repository = Repository::open(&bare)?;
+
+  repository.set_config("core.bare", "true")?;
+
+  repository
+    .register_worktree(worktree, head)
+    .context("failed to register worktree")?;
+
+  Worktree::adopt(&bare.join("index"), staging, worktree)
+}
+
+pub(crate) fn restore(root: &Path, staging: &Path) -> Result {
+  let git_dir = root.join(".git");
+
+  let bare = root.join(".bare");
+
+  if bare.is_dir() {
+    let mut registered = Vec::new();
+
+    for entry in fs::read_dir(root)? {
+      let path = entry?.path();
+
+      if path != git_dir && path != bare && path != staging {
+        registered.push(path);
+      }
+    }
+
+    if staging.join(".git").is_file() {
+      registered.push(staging.to_path_buf());
+    }
+
+    for path in registered {
+      let admin = Worktree::gitdir(&path).ok_or_else(|| {
+        anyhow!("`{}` was not created by the conversion", path.display())
+      })?;
+
+      if admin.join("index").is_file() && !bare.join("index").exists() {
+        fs::rename(admin.join("index"), bare.join("index"))
+          .context("failed to move the index back")?;
+      }
+
+      fs::remove_file(path.join(".git"))?;
+
+      if path != staging {
+        fs::remove_dir(&path)
+          .with_context(|| format!("failed to remove `{}`", path.display()))?;
+      }
+
+      fs::remove_dir_all(&admin)?;
+    }
+
+    if git_dir.is_file() {
+      fs::remove_file(&git_dir)?;
+    }
+
+    fs::rename(&bare, &git_dir).context("failed to move `.bare` to `.git`")?;
+
+    Repository::open(&git_dir)?.set_config("core.bare", "false")?;
+  }
+
+  for entry in fs::read_dir(staging)? {
+    let path = entry?.path();
+
+    fs::rename(&path, root.join(path.file_name().unwrap_or_default()))
+      .with_context(|| format!("failed to move `{}` back", path.display()))?;
+  }
+
+  fs::remove_dir(staging)
+    .with_context(|| format!("failed to remove `{}`", staging.display()))
+}
+
+pub(crate) fn run() -> Result {
+  let style = Style::stderr();
+
+  let current_dir = env::current_dir()?;
+
+  let repository = Repository::discover()?;
+
+  let worktrees = repository.worktrees()?;
+
+  let main = worktrees
+    .first()
+    .ok_or_else(|| anyhow!("no worktrees found"))?;
+
+  if main.bare {
+    bail!("repository is already bare");
+  }
+
+  let root = &main.path;
+
+  let git_dir = root.join(".git");
+
+  check(&git_dir)?;
+
+  let head = match &main.branch {
+    Some(branch) => branch.clone(),
+    None => main.head.clone().unwrap_or_default().into(),
+  };
+
+  let worktree = root.join(Worktree::branch_directory(
+    main.branch.as_deref().unwrap_or(OsStr::new("detached")),
+  ));
+
+  let staging = root.join(format!(".wt-converting-{}", process::id()));
+
+  fs::create_dir(&staging)
+    .with_context(|| format!("failed to create `{}`", staging.display()))?;
+
+  if let Err(error) = convert(root, &staging, &worktree, &head) {
+    if !staging.exists() && worktree.is_dir() {
+      let _ = fs::rename(&worktree, &staging);
+    }
+
+    return Err(match restore(root, &staging) {
+      Ok(()) => error.context(format!(
+        "failed to convert `{}`, changes were rolled back",
+        root.display()
+      )),
+      Err(restore_error) => error.context(format!(
+        "failed to convert `{}` and to roll back: {restore_error:#}\n\
+         the checkout is in `{}`, run `wt doctor` for details",
+        root.display(),
+        staging.display(),
+      )),
+    });
+  }
+
+  let repository = Repository::open(&root.join(".bare"))?;
+
+  let linked = worktrees[1..]
+    .iter()
+    .map(|worktree| worktree.path.clone())
+    .filter(|path| path.is_dir())
+    .collect::<Vec<_>>();
+
+  if !linked.is_empty() {
+    repository.repair_worktrees(&linked)?;
+  }
+
+  eprintln!(
+    "{} {} into {}",
+    style.apply(style::GREEN, "converted"),
+    style.apply(style::BOLD, root.display()),
+    style.apply(style::CYAN, worktree.display()),
+  );
+
+  if let Ok(relative) = current_dir.strip_prefix(root) {
+    bytes::print_path(
+      &worktree
+        .components()
+        .chain(relative.components())
+        .collect::<PathBuf>(),
+    )?;
+  }
+
+  Ok(())
+}
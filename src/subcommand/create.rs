@@ -1,85 +1,74 @@
-use {super::*, std::path::PathBuf};
+use super::*;
 
 #[derive(Debug, Parser)]
 pub(crate) struct Create {
   /// Branch name for the new worktree.
-  name: String,
+  name: OsString,
+  /// Only check out the directories in this sparse-checkout profile.
+  #[clap(long, value_name = "PROFILE")]
+  sparse: Option<String>,
 }
 
 impl Create {
   pub(crate) fn run(self) -> Result {
+    let config = Config::load()?;
+
     let style = Style::stdout();
 
-    let root = Command::new("git")
-      .args(["rev-parse", "--show-toplevel"])
-      .stderr(Stdio::null())
-      .output()?;
-
-    let root = Path::new(str::from_utf8(&root.stdout)?.trim());
-
-    let head_path = Command::new("git")
-      .args(["worktree", "list", "--porcelain"])
-      .stderr(Stdio::null())
-      .output()
-      .ok()
-      .filter(|output| output.status.success())
-      .and_then(|output| {
-        str::from_utf8(&output.stdout)
-          .ok()
-          .and_then(|stdout| stdout.split("\n\n").next())
-          .and_then(|block| Worktree::try_from(block).ok())
-          .map(|worktree| PathBuf::from(worktree.path))
-      })
-      .unwrap_or_else(|| root.to_path_buf());
-
-    let project = head_path.file_name().ok_or_else(|| {
-      anyhow!("failed to get project name from `{}`", head_path.display())
-    })?;
-
-    let dir_name = format!(
-      "{}.{}",
-      project.to_string_lossy(),
-      self.name.replace('/', "-")
-    );
+    let repository = Repository::discover()?;
+
+    #[cfg(unix)]
+    Journal::resume(&repository);
+
+    let provision = Provision::new(&config.create, &repository, style);
 
-    let worktree = head_path
-      .parent()
-      .ok_or_else(|| {
-        anyhow!(
-          "repo root `{}` has no parent directory",
-          head_path.display()
+    let sparse = self
+      .sparse
+      .as_deref()
+      .map(|profile| Ok::<_, Error>((profile, config.sparse_profile(profile)?)))
+      .transpose()?;
+
+    let worktree = repository.worktree_path(&self.name)?;
+
+    repository
+      .add_worktree(&worktree, &self.name, true, sparse.is_none())
+      .with_context(|| {
+        format!(
+          "failed to create worktree `{}`",
+          self.name.to_string_lossy()
         )
-      })?
-      .join(&dir_name);
-
-    let output = Command::new("git")
-      .args([
-        "worktree",
-        "add",
-        "-b",
-        &self.name,
-        &worktree.to_string_lossy(),
-      ])
-      .stdout(Stdio::null())
-      .stderr(Stdio::piped())
-      .output()?;
-
-    if !output.status.success() {
-      bail!(
-        "failed to create worktree `{}`: {}",
-        self.name,
-        str::from_utf8(&output.stderr)?.trim()
-      );
-    }
+      })?;
 
     eprintln!(
       "{} worktree {} at {}",
       style.apply(style::GREEN, "created"),
-      style.apply(style::BOLD, &self.name),
-      style.apply(style::CYAN, &dir_name),
+      style.apply(style::BOLD, self.name.to_string_lossy()),
+      style.apply(
+        style::CYAN,
+        worktree.file_name().unwrap_or_default().to_string_lossy()
+      ),
     );
 
-    println!("{}", worktree.display());
+    if let Some((profile, directories)) = sparse {
+      let checkout = repository.at(&worktree);
+
+      checkout
+        .sparse_checkout(profile, directories)
+        .and_then(|()| checkout.checkout())
+        .with_context(|| {
+          format!("failed to apply sparse profile `{profile}`")
+        })?;
+
+      eprintln!(
+        "{} sparse profile {}",
+        style.apply(style::GREEN, "applied"),
+        style.apply(style::BOLD, profile),
+      );
+    }
+
+    provision.apply(&worktree)?;
+
+    bytes::print_path(&worktree)?;
 
     Ok(())
   }
@@ -0,0 +1,85 @@
+use {super::*, std::fs::OpenOptions};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Delete {
+  /// File to append deletion failures to.
+  #[clap(long)]
+  log: PathBuf,
+  /// Directories to delete.
+  paths: Vec<PathBuf>,
+}
+
+impl Delete {
+  pub(crate) fn run(self) -> Result {
+    let failures = thread::scope(|scope| {
+      self
+        .paths
+        .iter()
+        .map(|path| scope.spawn(move || (path, fs::remove_dir_all(path))))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .filter_map(|handle| match handle.join() {
+          Ok((_, Ok(()))) => None,
+          Ok((_, Err(error))) if error.kind() == io::ErrorKind::NotFound => {
+            None
+          }
+          Ok((path, Err(error))) => {
+            Some(format!("failed to delete `{}`: {error}\n", path.display()))
+          }
+          Err(_) => Some("deletion thread panicked\n".into()),
+        })
+        .collect::<String>()
+    });
+
+    if failures.is_empty() {
+      return Ok(());
+    }
+
+    OpenOptions::new()
+      .append(true)
+      .create(true)
+      .open(&self.log)
+      .and_then(|mut file| file.write_all(failures.as_bytes()))
+      .with_context(|| format!("failed to write `{}`", self.log.display()))?;
+
+    let count = failures.lines().count();
+
+    bail!(
+      "failed to delete {count} director{}",
+      if count == 1 { "y" } else { "ies" }
+    );
+  }
+
+  #[cfg(unix)]
+  pub(crate) fn spawn(
+    dir: &Path,
+    log: &Path,
+    paths: &[PathBuf],
+  ) -> io::Result<()> {
+    let mut command = Command::new(env::current_exe()?);
+
+    command
+      .arg("delete")
+      .arg("--log")
+      .arg(log)
+      .arg("--")
+      .args(paths)
+      .current_dir(dir)
+      .stdin(Stdio::null())
+      .stdout(Stdio::null())
+      .stderr(Stdio::null());
+
+    // SAFETY: `setsid` is async-signal-safe and touches no parent state.
+    unsafe {
+      command.pre_exec(|| {
+        if libc::setsid() == -1 {
+          return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+      });
+    }
+
+    command.spawn().map(drop)
+  }
+}
@@ -0,0 +1,296 @@
+use {
+  super::*,
+  std::{collections::BTreeSet, iter::once, slice},
+};
+
+#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum Problem {
+  Broken {
+    admin: PathBuf,
+    path: PathBuf,
+    target: Option<PathBuf>,
+  },
+  Converting(PathBuf),
+  Moved {
+    from: PathBuf,
+    to: PathBuf,
+  },
+  Stale {
+    admin: PathBuf,
+    path: Option<PathBuf>,
+  },
+  Trash(PathBuf),
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct Doctor {
+  /// Fix the problems that are found.
+  #[clap(long)]
+  fix: bool,
+}
+
+impl Display for Problem {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Broken {
+        admin,
+        path,
+        target: Some(target),
+      } => write!(
+        f,
+        "`.git` file in `{}` points to `{}` instead of `{}`",
+        path.display(),
+        target.display(),
+        admin.display(),
+      ),
+      Self::Broken {
+        path, target: None, ..
+      } => write!(f, "`{}` has no valid `.git` file", path.display()),
+      Self::Converting(path) => write!(
+        f,
+        "`{}` was left behind by an interrupted `convert-repo`",
+        path.display(),
+      ),
+      Self::Moved { from, to } => write!(
+        f,
+        "worktree `{}` was moved to `{}`",
+        from.display(),
+        to.display(),
+      ),
+      Self::Stale {
+        admin,
+        path: Some(path),
+      } => write!(
+        f,
+        "worktree `{}` no longer exists, but `{}` still refers to it",
+        path.display(),
+        admin.display(),
+      ),
+      Self::Stale { admin, path: None } => write!(
+        f,
+        "`{}` does not record a worktree location",
+        admin.display(),
+      ),
+      Self::Trash(path) => write!(
+        f,
+        "`{}` was left behind by an interrupted removal",
+        path.display(),
+      ),
+    }
+  }
+}
+
+impl Problem {
+  fn fix(&self, repository: &Repository) -> Result {
+    match self {
+      Self::Broken {
+        admin,
+        path,
+        target: None,
+      } => {
+        let mut contents = b"gitdir: ".to_vec();
+
+        contents.extend_from_slice(&bytes::to_bytes(admin.as_os_str()));
+
+        contents.push(b'\n');
+
+        fs::write(path.join(".git"), contents).with_context(|| {
+          format!("failed to write `{}`", path.join(".git").display())
+        })
+      }
+      Self::Broken { path, .. } | Self::Moved { to: path, .. } => {
+        repository.repair_worktrees(slice::from_ref(path))
+      }
+      Self::Converting(path) => {
+        convert_repo::restore(path.parent().unwrap_or(path), path)
+          .with_context(|| format!("failed to restore `{}`", path.display()))
+      }
+      Self::Stale { .. } => repository.prune(),
+      Self::Trash(path) => fs::remove_dir_all(path)
+        .with_context(|| format!("failed to delete `{}`", path.display())),
+    }
+  }
+
+  fn remedy(&self) -> String {
+    match self {
+      Self::Broken {
+        admin,
+        path,
+        target: None,
+      } => format!(
+        "echo 'gitdir: {}' > {}",
+        admin.display(),
+        path.join(".git").display(),
+      ),
+      Self::Broken { path, .. } | Self::Moved { to: path, .. } => {
+        format!("git worktree repair {}", path.display())
+      }
+      Self::Converting(path) => {
+        let root = path.parent().unwrap_or(path);
+
+        let unbare = if root.join(".bare").is_dir() {
+          format!(
+            "rm {git} && mv {} {git} && git -C {} config core.bare false && ",
+            root.join(".bare").display(),
+            root.display(),
+            git = root.join(".git").display(),
+          )
+        } else {
+          String::new()
+        };
+
+        format!(
+          "{unbare}find {path} -mindepth 1 -maxdepth 1 -exec mv {{}} {} \\; \
+           && rmdir {path}",
+          root.display(),
+          path = path.display(),
+        )
+      }
+      Self::Stale { .. } => "git worktree prune".into(),
+      Self::Trash(path) => format!("rm -rf {}", path.display()),
+    }
+  }
+}
+
+impl Doctor {
+  fn diagnose(repository: &Repository) -> Result<Vec<Problem>> {
+    let main = repository.main_worktree()?;
+
+    let mut admins = Vec::new();
+
+    match fs::read_dir(repository.common_dir().join("worktrees")) {
+      Ok(entries) => {
+        for entry in entries {
+          let admin = entry?.path();
+
+          let path = fs::read(admin.join("gitdir")).ok().and_then(|contents| {
+            admin
+              .join(bytes::from_bytes(contents.trim_ascii_end()))
+              .parent()
+              .map(Path::to_path_buf)
+          });
+
+          admins.push((admin, path));
+        }
+      }
+      Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+      Err(error) => return Err(error.into()),
+    }
+
+    let candidates = once(main.path.as_path())
+      .chain(admins.iter().filter_map(|(_, path)| path.as_deref()))
+      .filter_map(Path::parent)
+      .chain(once(main.path.as_path()))
+      .collect::<BTreeSet<_>>()
+      .into_iter()
+      .filter_map(|directory| fs::read_dir(directory).ok())
+      .flatten()
+      .filter_map(|entry| {
+        let entry = entry.ok()?;
+        entry.file_type().ok()?.is_dir().then(|| entry.path())
+      })
+      .collect::<Vec<_>>();
+
+    let mut problems = Vec::new();
+
+    for (admin, path) in admins {
+      if let Some(path) = path.as_ref().filter(|path| path.is_dir()) {
+        let target = Worktree::gitdir(path);
+
+        if !target.as_ref().is_some_and(|target| same(target, &admin)) {
+          problems.push(Problem::Broken {
+            admin,
+            path: path.clone(),
+            target,
+          });
+        }
+
+        continue;
+      }
+
+      if let Some(to) = candidates.iter().find(|candidate| {
+        Worktree::gitdir(candidate).is_some_and(|target| same(&target, &admin))
+      }) {
+        problems.push(Problem::Moved {
+          from: path.unwrap_or_else(|| admin.clone()),
+          to: to.clone(),
+        });
+      } else if !admin.join("locked").exists() {
+        problems.push(Problem::Stale { admin, path });
+      }
+    }
+
+    for candidate in candidates {
+      let Some(name) = candidate.file_name() else {
+        continue;
+      };
+
+      let name = name.to_string_lossy();
+
+      if name.starts_with(".wt-converting-") {
+        problems.push(Problem::Converting(candidate));
+      } else if name.starts_with(".wt-removing-") {
+        problems.push(Problem::Trash(candidate));
+      }
+    }
+
+    problems.sort();
+
+    Ok(problems)
+  }
+
+  pub(crate) fn run(self) -> Result {
+    let style = Style::stderr();
+
+    let repository = Repository::discover()?;
+
+    #[cfg(unix)]
+    if self.fix {
+      Journal::resume(&repository);
+    }
+
+    let problems = Self::diagnose(&repository)?;
+
+    if problems.is_empty() {
+      eprintln!("{}", style.apply(style::GREEN, "no problems found"));
+      return Ok(());
+    }
+
+    for problem in &problems {
+      if self.fix {
+        problem.fix(&repository)?;
+
+        eprintln!("{}: {problem}", style.apply(style::GREEN, "fixed"));
+      } else {
+        eprintln!("{}: {problem}", style.apply(style::YELLOW, "problem"));
+        eprintln!(
+          "    {}: `{}`",
+          style.apply(style::CYAN, "fix"),
+          problem.remedy()
+        );
+      }
+    }
+
+    if !self.fix {
+      bail!(
+        "found {} problem{}, run `wt repair` to fix {}",
+        problems.len(),
+        if problems.len() == 1 { "" } else { "s" },
+        if problems.len() == 1 { "it" } else { "them" },
+      );
+    }
+
+    Ok(())
+  }
+}
+
+pub(crate) fn repair() -> Result {
+  Doctor { fix: true }.run()
+}
+
+fn same(a: &Path, b: &Path) -> bool {
+  match (a.canonicalize(), b.canonicalize()) {
+    (Ok(a), Ok(b)) => a == b,
+    _ => false,
+  }
+}
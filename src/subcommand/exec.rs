@@ -0,0 +1,205 @@
+use {
+  super::*,
+  glob::Pattern,
+  std::{
+    io::{BufRead, BufReader, Read},
+    num::NonZeroUsize,
+    process::ExitStatus,
+    sync::{
+      Mutex,
+      atomic::{AtomicUsize, Ordering},
+    },
+  },
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Exec {
+  /// Command to run, passed to the shell if it is a single argument.
+  #[clap(last = true, required = true)]
+  command: Vec<OsString>,
+  /// Only run in worktrees whose branch matches this glob.
+  #[clap(long)]
+  filter: Option<Pattern>,
+  /// Number of worktrees to run the command in at once.
+  #[clap(long, default_value = "1")]
+  parallel: NonZeroUsize,
+}
+
+impl Exec {
+  fn command(&self) -> Command {
+    if let [script] = self.command.as_slice() {
+      let mut command;
+
+      if cfg!(windows) {
+        command = Command::new("cmd");
+        command.arg("/C");
+      } else {
+        command = Command::new("sh");
+        command.arg("-c");
+      }
+
+      command.arg(script);
+
+      command
+    } else {
+      let mut command = Command::new(&self.command[0]);
+
+      command.args(&self.command[1..]);
+
+      command
+    }
+  }
+
+  fn execute(&self, worktree: &Worktree, width: usize) -> Result<ExitStatus> {
+    let mut child = self
+      .command()
+      .current_dir(&worktree.path)
+      .stdin(Stdio::null())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::piped())
+      .spawn()
+      .context("failed to run command")?;
+
+    let prefix = format!("{:<width$}", worktree.name());
+
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    thread::scope(|scope| {
+      if let Some(stdout) = stdout {
+        scope.spawn(|| {
+          forward(stdout, &prefix, io::stdout(), Style::stdout());
+        });
+      }
+
+      if let Some(stderr) = stderr {
+        scope.spawn(|| {
+          forward(stderr, &prefix, io::stderr(), Style::stderr());
+        });
+      }
+    });
+
+    Ok(child.wait()?)
+  }
+
+  pub(crate) fn run(self) -> Result {
+    let style = Style::stderr();
+
+    let repository = Repository::discover()?;
+
+    let worktrees = List::worktrees(&repository)?
+      .into_iter()
+      .filter(|worktree| !worktree.bare && worktree.path.is_dir())
+      .filter(|worktree| {
+        self
+          .filter
+          .as_ref()
+          .is_none_or(|filter| filter.matches(&worktree.name()))
+      })
+      .collect::<Vec<_>>();
+
+    if worktrees.is_empty() {
+      match &self.filter {
+        Some(filter) => bail!("no worktrees match `{filter}`"),
+        None => bail!("no worktrees found"),
+      }
+    }
+
+    let width = worktrees
+      .iter()
+      .map(|worktree| worktree.name().chars().count())
+      .max()
+      .unwrap_or_default();
+
+    let next = AtomicUsize::new(0);
+
+    let outcomes =
+      Mutex::new(worktrees.iter().map(|_| None).collect::<Vec<_>>());
+
+    thread::scope(|scope| {
+      for _ in 0..self.parallel.get().min(worktrees.len()) {
+        scope.spawn(|| {
+          loop {
+            let i = next.fetch_add(1, Ordering::Relaxed);
+
+            let Some(worktree) = worktrees.get(i) else {
+              break;
+            };
+
+            let outcome = self.execute(worktree, width);
+
+            outcomes.lock().unwrap()[i] = Some(outcome);
+          }
+        });
+      }
+    });
+
+    let mut failed = 0;
+
+    for (worktree, outcome) in worktrees
+      .iter()
+      .zip(outcomes.into_inner().map_err(|error| anyhow!("{error}"))?)
+    {
+      let status = match outcome {
+        Some(Ok(status)) if status.success() => {
+          style.apply(style::GREEN, "ok".to_string())
+        }
+        Some(Ok(status)) => {
+          failed += 1;
+
+          style.apply(
+            style::RED,
+            status.code().map_or_else(
+              || status.to_string(),
+              |code| format!("exit {code}"),
+            ),
+          )
+        }
+        Some(Err(error)) => {
+          failed += 1;
+
+          style.apply(style::RED, format!("{error:#}"))
+        }
+        None => continue,
+      };
+
+      eprintln!(
+        "{}  {status}",
+        style.apply(style::BOLD, format!("{:<width$}", worktree.name())),
+      );
+    }
+
+    if failed > 0 {
+      bail!(
+        "{failed} of {} command{} failed",
+        worktrees.len(),
+        if worktrees.len() == 1 { "" } else { "s" },
+      );
+    }
+
+    Ok(())
+  }
+}
+
+fn forward(
+  source: impl Read,
+  prefix: &str,
+  mut destination: impl Write,
+  style: Style,
+) {
+  let prefix = style.apply(style::CYAN, prefix);
+
+  for line in BufReader::new(source).split(b'\n') {
+    let Ok(line) = line else {
+      break;
+    };
+
+    let mut output = format!("{prefix} │ ").into_bytes();
+
+    output.extend_from_slice(&line);
+
+    output.push(b'\n');
+
+    let _ = destination.write_all(&output);
+  }
+}
@@ -0,0 +1,41 @@
+use super::*;
+
+#[cfg(not(unix))]
+pub(crate) fn run() -> Result {
+  Repository::discover()?;
+
+  eprintln!("nothing to clean up");
+
+  Ok(())
+}
+
+#[cfg(unix)]
+pub(crate) fn run() -> Result {
+  let config = Config::load()?;
+
+  let style = Style::stderr();
+
+  let repository = Repository::discover()?;
+
+  for failure in Journal::failures(&repository) {
+    eprintln!("{}: {failure}", style.apply(style::YELLOW, "warning"));
+  }
+
+  let recovered = Journal::recover(&repository)?;
+
+  let purged = Trash::purge(&repository, config.trash.retention_days)?;
+
+  for trash in &purged {
+    eprintln!(
+      "{} worktree {} from the trash",
+      style.apply(style::GREEN, "purged"),
+      style.apply(style::BOLD, trash.entry.name()),
+    );
+  }
+
+  if recovered == 0 && purged.is_empty() {
+    eprintln!("nothing to clean up");
+  }
+
+  Ok(())
+}
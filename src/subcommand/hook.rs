@@ -7,6 +7,9 @@ enum HookName {
 
 #[derive(Debug, Parser)]
 pub(crate) struct Hook {
+  /// Explain which hooks would run and why, instead of printing them.
+  #[clap(long)]
+  dry_run: bool,
   name: HookName,
 }
 
@@ -14,13 +17,25 @@ impl Hook {
   pub(crate) fn run(self) -> Result {
     let config = Config::load()?;
 
-    match self.name {
-      HookName::PostWorktreeChange => {
-        for entry in &config.hooks.post_worktree_change {
-          if entry.matches()? {
-            println!("{}", entry.command);
-          }
-        }
+    let current_dir = env::current_dir()?;
+
+    let event = match self.name {
+      HookName::PostWorktreeChange => "post-worktree-change",
+    };
+
+    if self.dry_run {
+      return hooks::explain(&current_dir, Some(event));
+    }
+
+    for entry in &config.hooks.post_worktree_change {
+      match entry.matches(&current_dir) {
+        Ok(true) => println!("{}", entry.command),
+        Ok(false) => {}
+        Err(error) => eprintln!(
+          "{}: skipping hook `{}`: {error:#}",
+          Style::stderr().apply(style::YELLOW, "warning"),
+          entry.command,
+        ),
       }
     }
 
@@ -0,0 +1,74 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) enum Hooks {
+  /// List configured hooks and whether they would run in a worktree.
+  List {
+    /// Branch of the worktree to check, defaults to the current worktree.
+    branch: Option<OsString>,
+  },
+}
+
+impl Hooks {
+  pub(crate) fn run(self) -> Result {
+    match self {
+      Self::List { branch } => {
+        let repository = Repository::discover()?;
+
+        let worktree = repository.find_worktree(branch.as_deref())?;
+
+        explain(&worktree.path, None)
+      }
+    }
+  }
+}
+
+pub(crate) fn explain(directory: &Path, event: Option<&str>) -> Result {
+  let config = Config::load()?;
+
+  let source = Config::source("hooks.post_worktree_change")?;
+
+  let style = Style::stdout();
+
+  for (name, entries) in config.hooks.events() {
+    if event.is_some_and(|event| event != name) {
+      continue;
+    }
+
+    println!("{}", style.apply(style::BOLD, name));
+
+    if entries.is_empty() {
+      println!("  no hooks configured");
+    }
+
+    for entry in entries {
+      println!("  {}", style.apply(style::CYAN, &entry.command));
+      println!("    source: {}", source.display());
+
+      let Some(pattern) = &entry.only_if else {
+        println!("    {}: no condition", style.apply(style::GREEN, "runs"));
+        continue;
+      };
+
+      println!("    condition: only_if `{pattern}`");
+
+      match entry.matches(directory) {
+        Ok(true) => println!(
+          "    {}: `{pattern}` matched in {}",
+          style.apply(style::GREEN, "runs"),
+          directory.display(),
+        ),
+        Ok(false) => println!(
+          "    {}: `{pattern}` matched no files in {}",
+          style.apply(style::YELLOW, "skipped"),
+          directory.display(),
+        ),
+        Err(error) => {
+          println!("    {}: {error:#}", style.apply(style::RED, "skipped"));
+        }
+      }
+    }
+  }
+
+  Ok(())
+}
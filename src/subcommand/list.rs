@@ -1,102 +1,352 @@
-use super::*;
-
-fn diff_stat(path: &str) -> (usize, usize) {
-  let output = Command::new("git")
-    .args(["diff", "--numstat"])
-    .current_dir(path)
-    .stderr(Stdio::null())
-    .output()
-    .ok();
-
-  let Some(output) = output.filter(|output| output.status.success()) else {
-    return (0, 0);
-  };
-
-  let stdout = str::from_utf8(&output.stdout).unwrap_or_default();
-
-  stdout
-    .lines()
-    .fold((0, 0), |(insertions, deletions), line| {
-      let mut parts = line.split('\t');
-
-      let added = parts
-        .next()
-        .and_then(|part| part.parse::<usize>().ok())
-        .unwrap_or(0);
-
-      let removed = parts
-        .next()
-        .and_then(|part| part.parse::<usize>().ok())
-        .unwrap_or(0);
-
-      (insertions + added, deletions + removed)
-    })
+use {
+  super::*,
+  std::time::{SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+enum Sort {
+  /// Least recently modified first.
+  Activity,
+  /// Oldest last commit first.
+  Age,
+  /// Fewest commits ahead of the main worktree first.
+  Ahead,
+  /// Alphabetically by branch name.
+  Name,
 }
 
-pub(crate) fn run() -> Result {
-  let style = Style::stdout();
+struct Context {
+  current_dir: PathBuf,
+  now: u64,
+  parent: Option<PathBuf>,
+  path_style: PathStyle,
+}
 
-  let current_dir = env::current_dir()?;
-  let current_dir = current_dir.canonicalize().unwrap_or(current_dir);
+struct Entry {
+  activity: Option<u64>,
+  ahead: usize,
+  committed: Option<u64>,
+  deletions: usize,
+  insertions: usize,
+  sparse: Option<String>,
+  subject: String,
+  worktree: Worktree,
+}
 
-  let output = Command::new("git")
-    .args(["worktree", "list", "--porcelain"])
-    .stderr(Stdio::null())
-    .output()?;
+#[derive(Debug, Parser)]
+pub(crate) struct List {
+  /// How to display worktree paths.
+  #[clap(long, value_enum)]
+  path: Option<PathStyle>,
+  /// Sort worktrees by the given key.
+  #[clap(long, value_enum)]
+  sort: Option<Sort>,
+  /// Print each worktree using a template, e.g. `{branch}\t{path}`.
+  #[clap(long)]
+  template: Option<Template>,
+}
 
-  if !output.status.success() {
-    bail!("failed to list worktrees");
+impl Sort {
+  fn column(self) -> Column {
+    match self {
+      Self::Activity => Column::Activity,
+      Self::Age => Column::Age,
+      Self::Ahead => Column::Ahead,
+      Self::Name => Column::Branch,
+    }
   }
+}
 
-  let worktrees = str::from_utf8(&output.stdout)?
-    .split("\n\n")
-    .filter_map(|block| Worktree::try_from(block).ok())
-    .filter(|worktree| Path::new(&worktree.path).is_dir())
-    .collect::<Vec<_>>();
+impl Entry {
+  fn cell(&self, column: Column, context: &Context) -> OsString {
+    let relative = |time: Option<u64>| {
+      time.map_or_else(|| "-".into(), |time| relative(context.now, time).into())
+    };
+
+    match column {
+      Column::Activity => relative(self.activity),
+      Column::Ahead => self.ahead.to_string().into(),
+      Column::Age => relative(self.committed),
+      Column::Branch => self
+        .worktree
+        .branch
+        .clone()
+        .unwrap_or_else(|| self.worktree.name().into_owned().into()),
+      Column::Diff => {
+        format!("+{}/-{}", self.insertions, self.deletions).into()
+      }
+      Column::Head => self.worktree.short_head().into(),
+      Column::Marker => {
+        let is_current = self
+          .worktree
+          .path
+          .canonicalize()
+          .is_ok_and(|path| context.current_dir.starts_with(path));
+
+        if is_current { "*" } else { " " }.into()
+      }
+      Column::Path => context
+        .path_style
+        .apply(&self.worktree.path, context.parent.as_deref())
+        .into_os_string(),
+      Column::Sparse => self.sparse.clone().unwrap_or_default().into(),
+      Column::State => self.worktree.state().into(),
+      Column::Subject => self.subject.clone().into(),
+    }
+  }
+
+  fn new(
+    repository: &Repository,
+    worktree: Worktree,
+    base: Option<&str>,
+    needs: impl Fn(Column) -> bool,
+  ) -> Self {
+    let checkout = repository.at(&worktree.path);
+
+    let (insertions, deletions) = needs(Column::Diff)
+      .then(|| checkout.diff_stat().ok())
+      .flatten()
+      .unwrap_or_default();
+
+    let (committed, subject) = (needs(Column::Age) || needs(Column::Subject))
+      .then(|| checkout.last_commit().ok())
+      .flatten()
+      .map_or((None, String::new()), |(time, subject)| {
+        (Some(time), subject)
+      });
+
+    Self {
+      activity: needs(Column::Activity)
+        .then(|| checkout.files().ok().and_then(last_modified))
+        .flatten(),
+      ahead: base
+        .filter(|_| needs(Column::Ahead))
+        .and_then(|base| checkout.ahead(base).ok())
+        .unwrap_or_default(),
+      committed,
+      deletions,
+      insertions,
+      sparse: needs(Column::Sparse)
+        .then(|| checkout.sparse_profile())
+        .flatten(),
+      subject,
+      worktree,
+    }
+  }
+
+  fn render(
+    &self,
+    column: Column,
+    cell: &str,
+    width: usize,
+    style: Style,
+  ) -> String {
+    match (column, column.code()) {
+      (Column::Diff, _) => format!(
+        "{}/{}{}",
+        style.apply(style::GREEN, format_args!("+{}", self.insertions)),
+        style.apply(style::RED, format_args!("-{}", self.deletions)),
+        " ".repeat(width.saturating_sub(cell.chars().count())),
+      ),
+      (_, Some(code)) => format!("{:<width$}", style.apply(code, cell)),
+      (_, None) => format!("{cell:<width$}"),
+    }
+  }
+}
+
+impl List {
+  pub(crate) fn run(self) -> Result {
+    let config = Config::load()?;
+
+    let style = Style::stdout();
+
+    let current_dir = env::current_dir()?;
+    let current_dir = current_dir.canonicalize().unwrap_or(current_dir);
+
+    let repository = Repository::discover()?;
+
+    let worktrees = Self::worktrees(&repository)?;
+
+    let Some(main) = worktrees.first() else {
+      bail!("no worktrees found");
+    };
+
+    let base = worktrees
+      .iter()
+      .find(|worktree| !worktree.bare)
+      .and_then(|worktree| worktree.head.clone());
+
+    let parent = main.path.parent().map(Path::to_path_buf);
+
+    let columns = config
+      .list
+      .columns
+      .unwrap_or_else(|| Column::DEFAULT.to_vec());
+
+    let needs = |column: Column| {
+      self.template.as_ref().map_or_else(
+        || columns.contains(&column),
+        |template| template.contains(column),
+      ) || self.sort.is_some_and(|sort| sort.column() == column)
+    };
+
+    let mut entries = worktrees
+      .into_iter()
+      .map(|worktree| Entry::new(&repository, worktree, base.as_deref(), needs))
+      .collect::<Vec<_>>();
+
+    match self.sort {
+      Some(Sort::Activity) => entries.sort_by_key(|entry| entry.activity),
+      Some(Sort::Age) => entries.sort_by_key(|entry| entry.committed),
+      Some(Sort::Ahead) => entries.sort_by_key(|entry| entry.ahead),
+      Some(Sort::Name) => {
+        entries.sort_by(|a, b| a.worktree.name().cmp(&b.worktree.name()));
+      }
+      None => {}
+    }
 
-  if worktrees.is_empty() {
-    bail!("no worktrees found");
+    let context = Context {
+      current_dir,
+      now: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+      parent,
+      path_style: self.path.unwrap_or(config.list.path),
+    };
+
+    if let Some(template) = &self.template {
+      let mut stdout = io::stdout().lock();
+
+      for entry in &entries {
+        let line = template.render(|column| entry.cell(column, &context));
+
+        stdout.write_all(&bytes::to_bytes(&line))?;
+
+        stdout.write_all(b"\n")?;
+      }
+
+      return Ok(());
+    }
+
+    print_table(&entries, &columns, &context, style);
+
+    Ok(())
   }
 
-  let stats = worktrees
+  pub(crate) fn worktrees(repository: &Repository) -> Result<Vec<Worktree>> {
+    Ok(
+      repository
+        .worktrees()?
+        .into_iter()
+        .filter(|worktree| {
+          worktree.prunable.is_some() || worktree.path.is_dir()
+        })
+        .collect(),
+    )
+  }
+}
+
+fn last_modified(files: Vec<PathBuf>) -> Option<u64> {
+  files
+    .into_iter()
+    .filter_map(|file| {
+      fs::metadata(file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+    })
+    .filter_map(|time| time.duration_since(UNIX_EPOCH).ok())
+    .map(|duration| duration.as_secs())
+    .max()
+}
+
+fn print_table(
+  entries: &[Entry],
+  columns: &[Column],
+  context: &Context,
+  style: Style,
+) {
+  let cells = entries
     .iter()
-    .map(|w| diff_stat(&w.path))
+    .map(|entry| {
+      columns
+        .iter()
+        .map(|column| {
+          entry.cell(*column, context).to_string_lossy().into_owned()
+        })
+        .collect::<Vec<_>>()
+    })
     .collect::<Vec<_>>();
 
-  let branch_width = worktrees
-    .iter()
-    .map(|worktree| worktree.branch.len())
-    .max()
-    .unwrap_or(0);
-
-  for (worktree, (insertions, deletions)) in worktrees.iter().zip(stats.iter())
-  {
-    let is_current = Path::new(&worktree.path)
-      .canonicalize()
-      .is_ok_and(|path| current_dir.starts_with(path));
-
-    let marker = if is_current {
-      style.apply(style::GREEN, "*")
-    } else {
-      style.apply(style::GREEN, " ")
-    };
+  let visible = (0..columns.len())
+    .filter(|&i| cells.iter().any(|row| !row[i].is_empty()))
+    .collect::<Vec<_>>();
+
+  let widths = cells.iter().fold(vec![0; columns.len()], |widths, row| {
+    widths
+      .into_iter()
+      .zip(row)
+      .map(|(width, cell)| width.max(cell.chars().count()))
+      .collect()
+  });
+
+  for (entry, row) in entries.iter().zip(&cells) {
+    let mut line = String::new();
+
+    for (position, &i) in visible.iter().enumerate() {
+      if position > 0 {
+        line.push_str(if columns[visible[position - 1]] == Column::Marker {
+          " "
+        } else {
+          "  "
+        });
+      }
+
+      let width = if position + 1 == visible.len() {
+        0
+      } else {
+        widths[i]
+      };
+
+      line.push_str(&entry.render(columns[i], &row[i], width, style));
+    }
 
-    let diff = format!(
-      "{}/{}",
-      style.apply(style::GREEN, format_args!("+{insertions}")),
-      style.apply(style::RED, format_args!("-{deletions}")),
-    );
-
-    println!(
-      "{} {:<width$}  {}  {}  {}",
-      marker,
-      style.apply(style::BOLD, &worktree.branch),
-      style.apply(style::CYAN, &worktree.head),
-      diff,
-      worktree.path,
-      width = branch_width,
-    );
+    println!("{line}");
   }
+}
+
+pub(crate) fn relative(now: u64, time: u64) -> String {
+  const MINUTE: u64 = 60;
+  const HOUR: u64 = 60 * MINUTE;
+  const DAY: u64 = 24 * HOUR;
+  const WEEK: u64 = 7 * DAY;
+  const MONTH: u64 = 30 * DAY;
+  const YEAR: u64 = 365 * DAY;
+
+  let elapsed = now.saturating_sub(time);
+
+  match elapsed {
+    0..MINUTE => "just now".to_string(),
+    MINUTE..HOUR => format!("{}m ago", elapsed / MINUTE),
+    HOUR..DAY => format!("{}h ago", elapsed / HOUR),
+    DAY..WEEK => format!("{}d ago", elapsed / DAY),
+    WEEK..MONTH => format!("{}w ago", elapsed / WEEK),
+    MONTH..YEAR => format!("{}mo ago", elapsed / MONTH),
+    _ => format!("{}y ago", elapsed / YEAR),
+  }
+}
 
-  Ok(())
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn relative_time() {
+    assert_eq!(relative(100, 100), "just now");
+    assert_eq!(relative(100, 200), "just now");
+    assert_eq!(relative(59, 0), "just now");
+    assert_eq!(relative(60, 0), "1m ago");
+    assert_eq!(relative(3 * 3600 + 5, 0), "3h ago");
+    assert_eq!(relative(2 * 86400, 0), "2d ago");
+    assert_eq!(relative(14 * 86400, 0), "2w ago");
+    assert_eq!(relative(90 * 86400, 0), "3mo ago");
+    assert_eq!(relative(800 * 86400, 0), "2y ago");
+  }
 }
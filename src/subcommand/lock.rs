@@ -0,0 +1,39 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Lock {
+  /// Branch of the worktree to lock, defaults to the current worktree.
+  branch: Option<OsString>,
+  /// Why the worktree is locked.
+  #[clap(long)]
+  reason: Option<String>,
+}
+
+impl Lock {
+  pub(crate) fn run(self) -> Result {
+    let style = Style::stderr();
+
+    let repository = Repository::discover()?;
+
+    let worktree = repository.find_worktree(self.branch.as_deref())?;
+
+    repository
+      .lock_worktree(&worktree.path, self.reason.as_deref())
+      .with_context(|| {
+        format!("failed to lock worktree `{}`", worktree.name())
+      })?;
+
+    eprint!(
+      "{} worktree {}",
+      style.apply(style::GREEN, "locked"),
+      style.apply(style::BOLD, worktree.name()),
+    );
+
+    match &self.reason {
+      Some(reason) => eprintln!(": {reason}"),
+      None => eprintln!(),
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,108 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Move {
+  /// New branch name.
+  #[clap(index = 2)]
+  new: OsString,
+  /// Branch of the worktree to move.
+  #[clap(index = 1)]
+  old: OsString,
+  /// Point the branch's upstream at the new name on the same remote.
+  #[clap(long)]
+  upstream: bool,
+}
+
+impl Move {
+  pub(crate) fn run(self) -> Result {
+    let style = Style::stderr();
+
+    let current_dir = env::current_dir()?;
+    let current_dir = current_dir.canonicalize().unwrap_or(current_dir);
+
+    let repository = Repository::discover()?;
+
+    let worktree = repository.find_worktree(Some(&self.old))?;
+
+    if worktree.path == repository.main_worktree()?.path {
+      bail!("cannot move the main worktree");
+    }
+
+    let remote = if self.upstream {
+      Some(
+        repository
+          .config(&format!("branch.{}.remote", self.old.to_string_lossy()))
+          .ok_or_else(|| {
+            anyhow!("branch `{}` has no upstream", self.old.to_string_lossy())
+          })?,
+      )
+    } else {
+      None
+    };
+
+    let source = worktree
+      .path
+      .canonicalize()
+      .unwrap_or_else(|_| worktree.path.clone());
+
+    let destination = repository.worktree_path(&self.new)?;
+
+    if destination.exists() {
+      bail!("`{}` already exists", destination.display());
+    }
+
+    repository
+      .move_worktree(&worktree.path, &destination)
+      .with_context(|| {
+        format!("failed to move worktree `{}`", self.old.to_string_lossy())
+      })?;
+
+    if let Err(error) = repository.rename_branch(&self.old, &self.new) {
+      repository.move_worktree(&destination, &worktree.path)?;
+      return Err(error);
+    }
+
+    let new = self.new.to_string_lossy();
+
+    if remote.is_some() {
+      repository.set_config(
+        &format!("branch.{new}.merge"),
+        &format!("refs/heads/{new}"),
+      )?;
+    }
+
+    eprintln!(
+      "{} worktree {} to {} at {}",
+      style.apply(style::GREEN, "moved"),
+      style.apply(style::BOLD, self.old.to_string_lossy()),
+      style.apply(style::BOLD, &new),
+      style.apply(
+        style::CYAN,
+        destination
+          .file_name()
+          .unwrap_or_default()
+          .to_string_lossy()
+      ),
+    );
+
+    if let Some(remote) = remote {
+      eprintln!(
+        "{} upstream of {} to {}",
+        style.apply(style::GREEN, "set"),
+        style.apply(style::BOLD, &new),
+        style.apply(style::BOLD, format!("{remote}/{new}")),
+      );
+    }
+
+    if let Ok(relative) = current_dir.strip_prefix(&source) {
+      bytes::print_path(
+        &destination
+          .components()
+          .chain(relative.components())
+          .collect::<PathBuf>(),
+      )?;
+    }
+
+    Ok(())
+  }
+}
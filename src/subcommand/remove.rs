@@ -1,169 +1,289 @@
 use super::*;
 
-#[cfg(unix)]
-fn remove_directories(
-  selected: &[(String, String)],
-  head_path: &str,
-) -> Result<Vec<PathBuf>> {
-  let mut pending = Vec::new();
-
-  for (i, (branch, path)) in selected.iter().enumerate() {
-    let worktree_path = Path::new(path);
-
-    let trash_path = worktree_path
-      .parent()
-      .unwrap_or(worktree_path)
-      .join(format!(".wt-removing-{}-{i}", process::id()));
+#[derive(Debug, Parser)]
+pub(crate) struct Remove {
+  /// Include locked worktrees.
+  #[clap(long, short)]
+  force: bool,
+  /// Move removed worktrees to the trash, so they can be brought back with
+  /// `wt restore`.
+  #[clap(long)]
+  trash: bool,
+}
 
-    if fs::rename(worktree_path, &trash_path).is_ok() {
-      pending.push(trash_path);
-    } else {
-      let result = Command::new("git")
-        .args(["worktree", "remove", "--force", "--force", path])
-        .stderr(Stdio::piped())
-        .output()?;
+#[cfg(not(unix))]
+impl Remove {
+  #[allow(clippy::unused_self)]
+  pub(crate) fn run(self) -> Result {
+    bail!("interactive selection is not supported on this platform");
+  }
+}
 
-      if !result.status.success() {
+#[cfg(unix)]
+impl Remove {
+  fn candidates(
+    &self,
+    worktrees: impl Iterator<Item = Worktree>,
+  ) -> Result<Vec<Worktree>> {
+    let (locked, unlocked) =
+      worktrees.partition::<Vec<_>, _>(|worktree| worktree.locked.is_some());
+
+    if !self.force {
+      if unlocked.is_empty() {
         bail!(
-          "failed to remove worktree `{}`: {}",
-          branch,
-          str::from_utf8(&result.stderr)?.trim()
+          "no worktrees to remove, {} locked worktree{} can be removed with \
+           `--force`",
+          locked.len(),
+          if locked.len() == 1 { "" } else { "s" },
         );
       }
+
+      return Ok(unlocked);
     }
+
+    Ok(unlocked.into_iter().chain(locked).collect())
   }
 
-  if !pending.is_empty() {
-    let prune = Command::new("git")
-      .current_dir(head_path)
-      .args(["worktree", "prune"])
-      .stderr(Stdio::piped())
-      .output()?;
-
-    if !prune.status.success() {
-      bail!(
-        "failed to prune worktrees: {}",
-        str::from_utf8(&prune.stderr)?.trim()
-      );
+  pub(crate) fn run(self) -> Result {
+    let config = Config::load()?;
+
+    let current_dir = env::current_dir()?;
+
+    let style = Style::stderr();
+
+    let repository = Repository::discover()?;
+
+    Journal::resume(&repository);
+
+    let worktrees = repository.worktrees()?;
+
+    let main = worktrees
+      .first()
+      .ok_or_else(|| anyhow!("no worktrees found"))?
+      .clone();
+
+    let checkouts = worktrees
+      .into_iter()
+      .filter(|worktree| !worktree.bare && worktree.path.is_dir())
+      .collect::<Vec<_>>();
+
+    let peers = checkouts
+      .iter()
+      .filter(|worktree| worktree.path != main.path)
+      .cloned()
+      .collect::<Vec<_>>();
+
+    if peers.is_empty() {
+      bail!("no worktrees to remove");
     }
-  }
 
-  Ok(pending)
-}
+    let selected = self.select(peers)?;
 
-#[cfg(not(unix))]
-pub(crate) fn run() -> Result {
-  bail!("interactive selection is not supported on this platform");
-}
+    if selected.is_empty() {
+      return Ok(());
+    }
 
-#[cfg(unix)]
-pub(crate) fn run() -> Result {
-  let current_dir = env::current_dir()?;
+    let trash = self.trash || config.trash.enabled;
 
-  let style = Style::stderr();
+    let trashes = if trash {
+      selected
+        .iter()
+        .enumerate()
+        .map(|(i, worktree)| Trash::new(&repository, worktree, i))
+        .collect::<Result<Vec<_>>>()?
+    } else {
+      Vec::new()
+    };
 
-  let output = Command::new("git")
-    .args(["worktree", "list", "--porcelain"])
-    .stderr(Stdio::null())
-    .output()?;
+    let entries = if trash {
+      trashes.iter().map(|trash| trash.entry.clone()).collect()
+    } else {
+      selected
+        .iter()
+        .enumerate()
+        .map(|(i, worktree)| journal::Entry {
+          branch: worktree.branch.clone(),
+          head: worktree.head.clone(),
+          locked: worktree.locked.clone(),
+          path: worktree.path.clone(),
+          trash: trash_path(worktree, i),
+        })
+        .collect::<Vec<_>>()
+    };
+
+    let journal = Journal::create(&repository, &entries)?;
+
+    let pending_deletes = if trash {
+      trash_directories(&repository, &selected, &trashes)?;
+      Vec::new()
+    } else {
+      remove_directories(&repository, &selected)?
+    };
 
-  if !output.status.success() {
-    bail!("failed to list worktrees");
-  }
+    for worktree in &selected {
+      eprintln!(
+        "{} worktree {} at {}",
+        style.apply(style::GREEN, if trash { "trashed" } else { "removed" }),
+        style.apply(style::BOLD, worktree.name()),
+        style.apply(style::CYAN, worktree.path.display()),
+      );
 
-  let worktrees = str::from_utf8(&output.stdout)?
-    .split("\n\n")
-    .filter_map(|block| Worktree::try_from(block).ok())
-    .filter(|worktree| Path::new(&worktree.path).is_dir())
-    .collect::<Vec<_>>();
+      if let Some(branch) = &worktree.branch {
+        repository.delete_branch(branch)?;
 
-  if worktrees.len() < 2 {
-    bail!("no worktrees to remove");
+        eprintln!(
+          "{} branch {}",
+          style.apply(style::GREEN, "deleted"),
+          style.apply(style::BOLD, worktree.name()),
+        );
+      }
+    }
+
+    if selected
+      .iter()
+      .any(|worktree| current_dir.starts_with(&worktree.path))
+    {
+      bytes::print_path(
+        checkouts
+          .iter()
+          .find(|worktree| !selected.contains(worktree))
+          .map_or(&main.path, |worktree| &worktree.path),
+      )?;
+    }
+
+    journal.finish()?;
+
+    detach(&repository, &pending_deletes);
+
+    Trash::purge(&repository, config.trash.retention_days)?;
+
+    Ok(())
   }
 
-  let head_path = worktrees[0].path.clone();
+  fn select(&self, peers: Vec<Worktree>) -> Result<Vec<Worktree>> {
+    let items = self
+      .candidates(peers.into_iter())?
+      .into_iter()
+      .map(|worktree| Arc::new(worktree) as Arc<dyn SkimItem>)
+      .collect::<Vec<Arc<dyn SkimItem>>>();
 
-  let items = worktrees
-    .into_iter()
-    .skip(1)
-    .map(|worktree| Arc::new(worktree) as Arc<dyn SkimItem>)
-    .collect::<Vec<Arc<dyn SkimItem>>>();
+    let options = SkimOptionsBuilder::default()
+      .multi(true)
+      .preview(Some("git -C {} diff --color=always".to_string()))
+      .build()?;
 
-  let options = SkimOptionsBuilder::default()
-    .multi(true)
-    .preview(Some("git -C {} diff --color=always".to_string()))
-    .build()?;
+    let (tx, rx): (SkimItemSender, SkimItemReceiver) = unbounded();
 
-  let (tx, rx): (SkimItemSender, SkimItemReceiver) = unbounded();
+    tx.send(items)?;
 
-  tx.send(items)?;
+    drop(tx);
 
-  drop(tx);
+    let output =
+      Skim::run_with(options, Some(rx)).map_err(|error| anyhow!("{error}"))?;
 
-  let output =
-    Skim::run_with(options, Some(rx)).map_err(|error| anyhow!("{error}"))?;
+    if output.is_abort {
+      return Ok(Vec::new());
+    }
 
-  if output.is_abort {
-    return Ok(());
+    Ok(
+      output
+        .selected_items
+        .iter()
+        .filter_map(|item| {
+          item.item.as_ref().as_any().downcast_ref::<Worktree>()
+        })
+        .cloned()
+        .collect(),
+    )
   }
+}
 
-  let selected = output
-    .selected_items
-    .iter()
-    .map(|item| (item.text().to_string(), item.output().to_string()))
-    .collect::<Vec<_>>();
-
-  if selected.is_empty() {
-    return Ok(());
+#[cfg(unix)]
+fn detach(repository: &Repository, paths: &[PathBuf]) {
+  if paths.is_empty() {
+    return;
   }
 
-  let pending_deletes = remove_directories(&selected, &head_path)?;
+  let spawned = delete::Delete::spawn(
+    repository.common_dir(),
+    &Journal::log(repository),
+    paths,
+  );
+
+  if spawned.is_err() {
+    thread::scope(|scope| {
+      for path in paths {
+        scope.spawn(move || {
+          let _ = fs::remove_dir_all(path);
+        });
+      }
+    });
+  }
+}
 
-  for (branch, path) in &selected {
-    eprintln!(
-      "{} worktree {} at {}",
-      style.apply(style::GREEN, "removed"),
-      style.apply(style::BOLD, branch),
-      style.apply(style::CYAN, path),
-    );
+#[cfg(unix)]
+fn remove_directories(
+  repository: &Repository,
+  selected: &[Worktree],
+) -> Result<Vec<PathBuf>> {
+  let mut pending = Vec::new();
 
-    if branch != "(detached)" {
-      let result = Command::new("git")
-        .current_dir(&head_path)
-        .args(["branch", "-D", branch])
-        .stderr(Stdio::piped())
-        .output()?;
+  for (i, worktree) in selected.iter().enumerate() {
+    unlock(repository, worktree)?;
 
-      if !result.status.success() {
-        bail!(
-          "failed to delete branch `{}`: {}",
-          branch,
-          str::from_utf8(&result.stderr)?.trim()
-        );
-      }
+    let trash_path = trash_path(worktree, i);
 
-      eprintln!(
-        "{} branch {}",
-        style.apply(style::GREEN, "deleted"),
-        style.apply(style::BOLD, branch),
-      );
+    if fs::rename(&worktree.path, &trash_path).is_ok() {
+      pending.push(trash_path);
+    } else {
+      repository
+        .remove_worktree(&worktree.path)
+        .with_context(|| {
+          format!("failed to remove worktree `{}`", worktree.name())
+        })?;
     }
   }
 
-  if selected
-    .iter()
-    .any(|(_, path)| current_dir.starts_with(path))
-  {
-    println!("{head_path}");
+  if !pending.is_empty() {
+    repository.prune()?;
   }
 
-  thread::scope(|scope| {
-    for path in &pending_deletes {
-      scope.spawn(move || {
-        let _ = fs::remove_dir_all(path);
-      });
-    }
-  });
+  Ok(pending)
+}
+
+#[cfg(unix)]
+fn trash_directories(
+  repository: &Repository,
+  selected: &[Worktree],
+  trashes: &[Trash],
+) -> Result {
+  for (worktree, trash) in selected.iter().zip(trashes) {
+    unlock(repository, worktree)?;
+
+    trash.store(repository, worktree)?;
+  }
+
+  repository.prune()
+}
+
+#[cfg(unix)]
+fn trash_path(worktree: &Worktree, i: usize) -> PathBuf {
+  worktree
+    .path
+    .parent()
+    .unwrap_or(&worktree.path)
+    .join(format!(".wt-removing-{}-{i}", process::id()))
+}
+
+#[cfg(unix)]
+fn unlock(repository: &Repository, worktree: &Worktree) -> Result {
+  if worktree.locked.is_some() {
+    repository
+      .unlock_worktree(&worktree.path)
+      .with_context(|| {
+        format!("failed to unlock worktree `{}`", worktree.name())
+      })?;
+  }
 
   Ok(())
 }
@@ -0,0 +1,85 @@
+use super::*;
+
+#[cfg(unix)]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Restore {
+  /// Branch of the removed worktree to restore, lists the trash if omitted.
+  branch: Option<OsString>,
+}
+
+#[cfg(not(unix))]
+impl Restore {
+  #[allow(clippy::unused_self)]
+  pub(crate) fn run(self) -> Result {
+    bail!("the trash is not supported on this platform");
+  }
+}
+
+#[cfg(unix)]
+impl Restore {
+  fn list(trash: &[Trash]) -> Result {
+    if trash.is_empty() {
+      eprintln!("trash is empty");
+      return Ok(());
+    }
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+    let width = trash
+      .iter()
+      .map(|trash| trash.entry.name().chars().count())
+      .max()
+      .unwrap_or_default();
+
+    for trash in trash.iter().rev() {
+      println!(
+        "{:<width$}  {:<8}  {}",
+        trash.entry.name(),
+        list::relative(now, trash.removed),
+        trash.entry.path.display(),
+      );
+    }
+
+    Ok(())
+  }
+
+  pub(crate) fn run(self) -> Result {
+    let style = Style::stderr();
+
+    let repository = Repository::discover()?;
+
+    let trash = Trash::list(&repository)?;
+
+    let Some(branch) = self.branch else {
+      return Self::list(&trash);
+    };
+
+    let Some(trash) = trash
+      .iter()
+      .rev()
+      .find(|trash| trash.entry.branch.as_ref() == Some(&branch))
+    else {
+      bail!(
+        "no removed worktree found for branch `{}`",
+        branch.to_string_lossy()
+      );
+    };
+
+    trash.restore(&repository).with_context(|| {
+      format!("failed to restore worktree `{}`", trash.entry.name())
+    })?;
+
+    eprintln!(
+      "{} worktree {} at {}",
+      style.apply(style::GREEN, "restored"),
+      style.apply(style::BOLD, trash.entry.name()),
+      style.apply(style::CYAN, trash.entry.path.display()),
+    );
+
+    bytes::print_path(&trash.entry.path)?;
+
+    Ok(())
+  }
+}
@@ -0,0 +1,41 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Sparse {
+  /// Sparse-checkout profile to apply to the current worktree.
+  profile: String,
+}
+
+impl Sparse {
+  pub(crate) fn run(self) -> Result {
+    let config = Config::load()?;
+
+    let style = Style::stderr();
+
+    let directories = config.sparse_profile(&self.profile)?;
+
+    let repository = Repository::discover()?;
+
+    let worktree = repository.find_worktree(None)?;
+
+    if worktree.bare {
+      bail!("cannot apply a sparse profile to a bare repository");
+    }
+
+    repository
+      .at(&worktree.path)
+      .sparse_checkout(&self.profile, directories)
+      .with_context(|| {
+        format!("failed to apply sparse profile `{}`", self.profile)
+      })?;
+
+    eprintln!(
+      "{} sparse profile {} to worktree {}",
+      style.apply(style::GREEN, "applied"),
+      style.apply(style::BOLD, &self.profile),
+      style.apply(style::BOLD, worktree.name()),
+    );
+
+    Ok(())
+  }
+}
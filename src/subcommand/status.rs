@@ -0,0 +1,88 @@
+use super::*;
+
+fn divergence(checkout: &Checkout, base: &str) -> String {
+  match (checkout.ahead(base), checkout.behind(base)) {
+    (Ok(ahead), Ok(behind)) => {
+      format!("{base}, {ahead} ahead, {behind} behind")
+    }
+    _ => base.into(),
+  }
+}
+
+pub(crate) fn run() -> Result {
+  let style = Style::stdout();
+
+  let repository = Repository::discover()?;
+
+  let worktree = repository.find_worktree(None)?;
+
+  let path = &worktree.path;
+
+  let checkout = repository.at(path);
+
+  let mut rows = vec![(
+    "branch",
+    match &worktree.branch {
+      Some(branch) => style
+        .apply(style::BOLD, branch.to_string_lossy())
+        .to_string(),
+      None => format!("{} at {}", worktree.name(), worktree.short_head()),
+    },
+  )];
+
+  if let Some(branch) = &worktree.branch {
+    rows.push((
+      "upstream",
+      repository.upstream(branch).map_or_else(
+        || "none".into(),
+        |upstream| divergence(&checkout, &upstream),
+      ),
+    ));
+  }
+
+  let default = repository.default_base()?;
+
+  rows.push(("default", divergence(&checkout, &default)));
+
+  let (staged, unstaged, untracked) = checkout.changes()?;
+
+  rows.push((
+    "changes",
+    if staged + unstaged + untracked == 0 {
+      style.apply(style::GREEN, "clean").to_string()
+    } else {
+      format!("{staged} staged, {unstaged} unstaged, {untracked} untracked")
+    },
+  ));
+
+  rows.push((
+    "lock",
+    match &worktree.locked {
+      Some(_) => style.apply(style::RED, worktree.state()).to_string(),
+      None => "unlocked".into(),
+    },
+  ));
+
+  rows.push((
+    "main",
+    style
+      .apply(style::CYAN, repository.main_worktree()?.path.display())
+      .to_string(),
+  ));
+
+  let mut previous = "";
+
+  for (label, value) in rows {
+    if label == previous {
+      println!("{:10}{value}", "");
+    } else {
+      println!("{label:10}{value}");
+    }
+
+    previous = label;
+  }
+
+  println!();
+
+  hooks::explain(path, None)
+}
@@ -7,19 +7,10 @@ pub(crate) fn run() -> Result {
 
 #[cfg(unix)]
 pub(crate) fn run() -> Result {
-  let output = Command::new("git")
-    .args(["worktree", "list", "--porcelain"])
-    .stderr(Stdio::null())
-    .output()?;
-
-  if !output.status.success() {
-    bail!("failed to list worktrees");
-  }
-
-  let worktrees = str::from_utf8(&output.stdout)?
-    .split("\n\n")
-    .filter_map(|block| Worktree::try_from(block).ok())
-    .filter(|worktree| Path::new(&worktree.path).is_dir())
+  let worktrees = Repository::discover()?
+    .worktrees()?
+    .into_iter()
+    .filter(|worktree| !worktree.bare && worktree.path.is_dir())
     .collect::<Vec<_>>();
 
   if worktrees.is_empty() {
@@ -48,8 +39,12 @@ pub(crate) fn run() -> Result {
     return Ok(());
   }
 
-  if let Some(item) = output.selected_items.first() {
-    println!("{}", item.output());
+  if let Some(worktree) = output
+    .selected_items
+    .first()
+    .and_then(|item| item.item.as_ref().as_any().downcast_ref::<Worktree>())
+  {
+    bytes::print_path(&worktree.path)?;
   }
 
   Ok(())
@@ -0,0 +1,230 @@
+use super::*;
+
+enum Outcome {
+  Conflict(usize),
+  Failed(String),
+  Skipped(&'static str),
+  Synced {
+    after: String,
+    before: String,
+    commits: usize,
+  },
+  UpToDate,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct Sync {
+  /// Revision to sync onto, defaults to the upstream of the default branch.
+  #[clap(long)]
+  base: Option<String>,
+  /// Whether to rebase branches onto the base or merge it into them.
+  #[clap(long, value_enum)]
+  strategy: Option<Strategy>,
+}
+
+impl Outcome {
+  fn detail(&self, strategy: Strategy) -> String {
+    match self {
+      Self::Conflict(files) => format!(
+        "{files} conflicting file{}, resolve and run `git {} --continue`",
+        if *files == 1 { "" } else { "s" },
+        match strategy {
+          Strategy::Merge => "merge",
+          Strategy::Rebase => "rebase",
+        },
+      ),
+      Self::Failed(error) => error.clone(),
+      Self::Skipped(reason) => (*reason).to_string(),
+      Self::Synced {
+        after,
+        before,
+        commits,
+      } => format!(
+        "{} → {}, {commits} new commit{}",
+        &before[..before.len().min(7)],
+        &after[..after.len().min(7)],
+        if *commits == 1 { "" } else { "s" },
+      ),
+      Self::UpToDate => String::new(),
+    }
+  }
+
+  fn failed(&self) -> bool {
+    matches!(self, Self::Conflict(_) | Self::Failed(_))
+  }
+
+  fn label(&self, strategy: Strategy) -> (&'static str, Option<&'static str>) {
+    match self {
+      Self::Conflict(_) => ("conflict", Some(style::RED)),
+      Self::Failed(_) => ("failed", Some(style::RED)),
+      Self::Skipped(_) => ("skipped", Some(style::YELLOW)),
+      Self::Synced { .. } => match strategy {
+        Strategy::Merge => ("merged", Some(style::GREEN)),
+        Strategy::Rebase => ("rebased", Some(style::GREEN)),
+      },
+      Self::UpToDate => ("up to date", None),
+    }
+  }
+}
+
+impl Sync {
+  fn base(&self, config: &Config, repository: &Repository) -> Result<String> {
+    if let Some(base) = self.base.as_ref().or(config.sync.base.as_ref()) {
+      return Ok(base.clone());
+    }
+
+    repository.default_base()
+  }
+
+  pub(crate) fn run(self) -> Result {
+    let config = Config::load()?;
+
+    let style = Style::stdout();
+
+    let warning = Style::stderr();
+
+    let repository = Repository::discover()?;
+
+    let base = self.base(&config, &repository)?;
+
+    let strategy = self.strategy.unwrap_or(config.sync.strategy);
+
+    let worktrees = List::worktrees(&repository)?
+      .into_iter()
+      .filter(|worktree| !worktree.bare && worktree.path.is_dir())
+      .collect::<Vec<_>>();
+
+    let Some(first) = worktrees.first() else {
+      bail!("no worktrees found");
+    };
+
+    if repository.at(&first.path).resolve(&base).is_err() {
+      bail!("unknown base `{base}`");
+    }
+
+    let outcomes = worktrees
+      .iter()
+      .map(|worktree| {
+        let outcome = sync(&repository, worktree, &base, strategy);
+
+        if let Outcome::Skipped(reason) = &outcome {
+          eprintln!(
+            "{}: skipping `{}`: {reason}",
+            warning.apply(style::YELLOW, "warning"),
+            worktree.name(),
+          );
+        }
+
+        outcome
+      })
+      .collect::<Vec<_>>();
+
+    let rows = worktrees
+      .iter()
+      .zip(&outcomes)
+      .map(|(worktree, outcome)| {
+        (
+          worktree.name(),
+          outcome.label(strategy),
+          outcome.detail(strategy),
+        )
+      })
+      .collect::<Vec<_>>();
+
+    let name_width = rows
+      .iter()
+      .map(|(name, ..)| name.chars().count())
+      .max()
+      .unwrap_or_default();
+
+    let label_width = rows
+      .iter()
+      .map(|(_, (label, _), _)| label.len())
+      .max()
+      .unwrap_or_default();
+
+    for (name, (label, code), detail) in rows {
+      let label = format!("{label:<label_width$}");
+
+      let label = match code {
+        Some(code) => style.apply(code, label).to_string(),
+        None => label,
+      };
+
+      println!(
+        "{}",
+        format!("{name:<name_width$}  {label}  {detail}").trim_end()
+      );
+    }
+
+    let failed = outcomes.iter().filter(|outcome| outcome.failed()).count();
+
+    if failed > 0 {
+      bail!(
+        "failed to sync {failed} worktree{}",
+        if failed == 1 { "" } else { "s" }
+      );
+    }
+
+    Ok(())
+  }
+}
+
+fn sync(
+  repository: &Repository,
+  worktree: &Worktree,
+  base: &str,
+  strategy: Strategy,
+) -> Outcome {
+  let checkout = repository.at(&worktree.path);
+
+  if worktree.branch.is_none() {
+    return Outcome::Skipped("detached HEAD");
+  }
+
+  match checkout.dirty() {
+    Ok(true) => return Outcome::Skipped("uncommitted changes"),
+    Ok(false) => {}
+    Err(error) => return Outcome::Failed(error.to_string()),
+  }
+
+  let (before, commits) = match checkout
+    .resolve("HEAD")
+    .and_then(|head| Ok((head, checkout.behind(base)?)))
+  {
+    Ok(state) => state,
+    Err(error) => return Outcome::Failed(error.to_string()),
+  };
+
+  if commits == 0 {
+    return Outcome::UpToDate;
+  }
+
+  let result = match strategy {
+    Strategy::Merge => checkout.merge(base),
+    Strategy::Rebase => checkout.rebase(base),
+  };
+
+  if let Err(error) = result {
+    return match checkout.conflicts() {
+      Ok(files) if files > 0 => Outcome::Conflict(files),
+      _ => Outcome::Failed(
+        error
+          .to_string()
+          .lines()
+          .next()
+          .unwrap_or_default()
+          .to_string(),
+      ),
+    };
+  }
+
+  match checkout.resolve("HEAD") {
+    Ok(after) => Outcome::Synced {
+      after,
+      before,
+      commits,
+    },
+    Err(error) => Outcome::Failed(error.to_string()),
+  }
+}
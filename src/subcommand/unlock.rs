@@ -0,0 +1,35 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Unlock {
+  /// Branch of the worktree to unlock, defaults to the current worktree.
+  branch: Option<OsString>,
+}
+
+impl Unlock {
+  pub(crate) fn run(self) -> Result {
+    let style = Style::stderr();
+
+    let repository = Repository::discover()?;
+
+    let worktree = repository.find_worktree(self.branch.as_deref())?;
+
+    if worktree.locked.is_none() {
+      bail!("worktree `{}` is not locked", worktree.name());
+    }
+
+    repository
+      .unlock_worktree(&worktree.path)
+      .with_context(|| {
+        format!("failed to unlock worktree `{}`", worktree.name())
+      })?;
+
+    eprintln!(
+      "{} worktree {}",
+      style.apply(style::GREEN, "unlocked"),
+      style.apply(style::BOLD, worktree.name()),
+    );
+
+    Ok(())
+  }
+}
@@ -0,0 +1,133 @@
+use super::*;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Segment {
+  Column(Column),
+  Literal(String),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Template(Vec<Segment>);
+
+impl Template {
+  pub(crate) fn contains(&self, column: Column) -> bool {
+    self.0.contains(&Segment::Column(column))
+  }
+
+  pub(crate) fn render(
+    &self,
+    mut cell: impl FnMut(Column) -> OsString,
+  ) -> OsString {
+    let mut rendered = OsString::new();
+
+    for segment in &self.0 {
+      match segment {
+        Segment::Column(column) => rendered.push(cell(*column)),
+        Segment::Literal(literal) => rendered.push(literal),
+      }
+    }
+
+    rendered
+  }
+}
+
+impl FromStr for Template {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut segments = Vec::new();
+
+    let mut literal = String::new();
+
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+      match c {
+        '\\' => match chars.next() {
+          Some('n') => literal.push('\n'),
+          Some('t') => literal.push('\t'),
+          Some('\\') | None => literal.push('\\'),
+          Some(other) => {
+            literal.push('\\');
+            literal.push(other);
+          }
+        },
+        '{' => {
+          let mut name = String::new();
+
+          loop {
+            match chars.next() {
+              Some('}') => break,
+              Some(c) => name.push(c),
+              None => bail!("unterminated `{{` in template"),
+            }
+          }
+
+          if !literal.is_empty() {
+            segments.push(Segment::Literal(mem::take(&mut literal)));
+          }
+
+          let name = name.trim();
+
+          segments.push(Segment::Column(
+            clap::ValueEnum::from_str(name, false)
+              .map_err(|_| anyhow!("unknown column `{name}`"))?,
+          ));
+        }
+        '}' => bail!("unmatched `}}` in template"),
+        c => literal.push(c),
+      }
+    }
+
+    if !literal.is_empty() {
+      segments.push(Segment::Literal(literal));
+    }
+
+    Ok(Self(segments))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn escapes() {
+    assert_eq!(
+      "{branch}\\t{path}\\n".parse::<Template>().unwrap(),
+      Template(vec![
+        Segment::Column(Column::Branch),
+        Segment::Literal("\t".to_string()),
+        Segment::Column(Column::Path),
+        Segment::Literal("\n".to_string()),
+      ]),
+    );
+  }
+
+  #[test]
+  fn render() {
+    assert_eq!(
+      "{branch} at {path}"
+        .parse::<Template>()
+        .unwrap()
+        .render(|column| format!("{column:?}").into()),
+      "Branch at Path",
+    );
+  }
+
+  #[test]
+  fn unknown_column() {
+    assert_eq!(
+      "{nope}".parse::<Template>().unwrap_err().to_string(),
+      "unknown column `nope`",
+    );
+  }
+
+  #[test]
+  fn unterminated() {
+    assert_eq!(
+      "{branch".parse::<Template>().unwrap_err().to_string(),
+      "unterminated `{` in template",
+    );
+  }
+}
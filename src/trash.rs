@@ -0,0 +1,278 @@
+use {
+  super::*,
+  journal::Entry,
+  std::{
+    os::unix::fs::MetadataExt,
+    time::{SystemTime, UNIX_EPOCH},
+  },
+};
+
+const DIRECTORY: &str = "wt/trash";
+
+#[derive(Debug)]
+pub(crate) struct Trash {
+  pub(crate) entry: Entry,
+  id: String,
+  pub(crate) removed: u64,
+}
+
+impl Trash {
+  fn delete(&self, repository: &Repository) -> Result {
+    if self.entry.head.is_some() {
+      repository.delete_ref(&self.reference())?;
+    }
+
+    if self.entry.trash.exists() {
+      fs::remove_dir_all(&self.entry.trash).with_context(|| {
+        format!("failed to delete `{}`", self.entry.trash.display())
+      })?;
+    }
+
+    let directory = Self::directory(repository).join(&self.id);
+
+    fs::remove_dir_all(&directory)
+      .with_context(|| format!("failed to delete `{}`", directory.display()))
+  }
+
+  fn directory(repository: &Repository) -> PathBuf {
+    repository.common_dir().join(DIRECTORY)
+  }
+
+  pub(crate) fn discard(&self, repository: &Repository) -> Result {
+    let directory = Self::directory(repository).join(&self.id);
+
+    if !directory.exists() {
+      return Ok(());
+    }
+
+    fs::remove_dir_all(&directory)
+      .with_context(|| format!("failed to delete `{}`", directory.display()))
+  }
+
+  pub(crate) fn interrupted(
+    repository: &Repository,
+    entry: &Entry,
+  ) -> Option<Self> {
+    let id = if entry.trash.file_name()? == "worktree"
+      && entry.trash.parent()?.parent()? == Self::directory(repository)
+    {
+      entry.trash.parent()?.file_name()?.to_str()?
+    } else {
+      entry
+        .trash
+        .file_name()?
+        .to_str()?
+        .strip_prefix(".wt-trash-")?
+    };
+
+    let removed = id.split('-').next()?.parse().ok()?;
+
+    Some(Self {
+      entry: entry.clone(),
+      id: id.to_string(),
+      removed,
+    })
+  }
+
+  pub(crate) fn list(repository: &Repository) -> Result<Vec<Self>> {
+    let Ok(entries) = fs::read_dir(Self::directory(repository)) else {
+      return Ok(Vec::new());
+    };
+
+    let mut trash = Vec::new();
+
+    for entry in entries {
+      let directory = entry?.path();
+
+      let Some(id) = directory.file_name().and_then(|name| name.to_str())
+      else {
+        continue;
+      };
+
+      let Some(removed) = id
+        .split('-')
+        .next()
+        .and_then(|secs| secs.parse::<u64>().ok())
+      else {
+        continue;
+      };
+
+      let Ok(contents) = fs::read(directory.join("entry")) else {
+        continue;
+      };
+
+      if let Some(entry) = Entry::parse(&contents).into_iter().next() {
+        trash.push(Self {
+          entry,
+          id: id.to_string(),
+          removed,
+        });
+      }
+    }
+
+    trash.sort_by(|a, b| (a.removed, &a.id).cmp(&(b.removed, &b.id)));
+
+    Ok(trash)
+  }
+
+  pub(crate) fn new(
+    repository: &Repository,
+    worktree: &Worktree,
+    i: usize,
+  ) -> Result<Self> {
+    let removed = now()?;
+
+    let id = format!("{removed}-{}-{i}", process::id());
+
+    let same_device = fs::metadata(&worktree.path)
+      .and_then(|worktree| {
+        Ok(worktree.dev() == fs::metadata(repository.common_dir())?.dev())
+      })
+      .unwrap_or_default();
+
+    let trash = if same_device {
+      Self::directory(repository).join(&id).join("worktree")
+    } else {
+      worktree
+        .path
+        .parent()
+        .unwrap_or(&worktree.path)
+        .join(format!(".wt-trash-{id}"))
+    };
+
+    let head = worktree
+      .head
+      .clone()
+      .filter(|head| head.bytes().any(|byte| byte != b'0'));
+
+    Ok(Self {
+      entry: Entry {
+        branch: worktree.branch.clone(),
+        head,
+        locked: worktree.locked.clone(),
+        path: worktree.path.clone(),
+        trash,
+      },
+      id,
+      removed,
+    })
+  }
+
+  pub(crate) fn purge(
+    repository: &Repository,
+    retention_days: u64,
+  ) -> Result<Vec<Self>> {
+    let cutoff = now()?.saturating_sub(retention_days * 24 * 60 * 60);
+
+    let expired = Self::list(repository)?
+      .into_iter()
+      .filter(|trash| trash.removed < cutoff)
+      .collect::<Vec<_>>();
+
+    for trash in &expired {
+      trash.delete(repository)?;
+    }
+
+    Ok(expired)
+  }
+
+  pub(crate) fn record(&self, repository: &Repository) -> Result {
+    if let Some(head) = &self.entry.head {
+      repository.update_ref(&self.reference(), head)?;
+    }
+
+    let mut contents = Vec::new();
+
+    self.entry.serialize(&mut contents);
+
+    fs::write(
+      Self::directory(repository).join(&self.id).join("entry"),
+      contents,
+    )
+    .context("failed to write trash entry")
+  }
+
+  fn reference(&self) -> String {
+    format!("refs/wt-trash/{}", self.id)
+  }
+
+  pub(crate) fn restore(&self, repository: &Repository) -> Result {
+    let path = &self.entry.path;
+
+    if path.exists() {
+      bail!("`{}` already exists", path.display());
+    }
+
+    let head = match (&self.entry.branch, &self.entry.head) {
+      (Some(branch), _) => {
+        if repository
+          .at(repository.common_dir())
+          .resolve(&format!("refs/heads/{}", branch.to_string_lossy()))
+          .is_ok()
+        {
+          bail!("branch `{}` already exists", branch.to_string_lossy());
+        }
+
+        repository.create_branch(branch, &self.reference())?;
+
+        branch.clone()
+      }
+      (None, Some(head)) => head.into(),
+      (None, None) => bail!("`{}` has no commit to restore", self.id),
+    };
+
+    repository
+      .register_worktree(path, &head)
+      .context("failed to register worktree")?;
+
+    Worktree::adopt(
+      &Self::directory(repository).join(&self.id).join("index"),
+      &self.entry.trash,
+      path,
+    )?;
+
+    if let Some(reason) = &self.entry.locked {
+      repository.lock_worktree(
+        path,
+        Some(reason.as_str()).filter(|reason| !reason.is_empty()),
+      )?;
+    }
+
+    self.delete(repository)
+  }
+
+  pub(crate) fn store(
+    &self,
+    repository: &Repository,
+    worktree: &Worktree,
+  ) -> Result {
+    let directory = Self::directory(repository).join(&self.id);
+
+    fs::create_dir_all(&directory)
+      .with_context(|| format!("failed to create `{}`", directory.display()))?;
+
+    if let Some(admin) = Worktree::gitdir(&worktree.path) {
+      let index = admin.join("index");
+
+      if index.exists() {
+        fs::copy(&index, directory.join("index"))
+          .context("failed to save the index")?;
+      }
+    }
+
+    if let Err(error) = fs::rename(&worktree.path, &self.entry.trash) {
+      self.discard(repository)?;
+
+      return Err(Error::from(error).context(format!(
+        "failed to move worktree `{}` to the trash",
+        worktree.name()
+      )));
+    }
+
+    self.record(repository)
+  }
+}
+
+fn now() -> Result<u64> {
+  Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
@@ -1,52 +1,173 @@
 use super::*;
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub(crate) struct Worktree {
-  pub(crate) branch: String,
-  pub(crate) head: String,
-  pub(crate) path: String,
+  pub(crate) bare: bool,
+  pub(crate) branch: Option<OsString>,
+  pub(crate) detached: bool,
+  pub(crate) head: Option<String>,
+  pub(crate) locked: Option<String>,
+  pub(crate) path: PathBuf,
+  pub(crate) prunable: Option<String>,
 }
 
-impl TryFrom<&str> for Worktree {
+impl Worktree {
+  pub(crate) fn adopt(index: &Path, staging: &Path, worktree: &Path) -> Result {
+    let admin = Self::gitdir(worktree).ok_or_else(|| {
+      anyhow!("`{}` has no valid `.git` file", worktree.display())
+    })?;
+
+    fs::rename(worktree.join(".git"), staging.join(".git"))?;
+
+    fs::remove_dir(worktree)?;
+
+    fs::rename(staging, worktree).with_context(|| {
+      format!("failed to move checkout to `{}`", worktree.display())
+    })?;
+
+    if index.exists() {
+      fs::rename(index, admin.join("index"))
+        .context("failed to move the index")?;
+    }
+
+    Ok(())
+  }
+
+  pub(crate) fn branch_directory(branch: &OsStr) -> OsString {
+    bytes::from_bytes(
+      &bytes::to_bytes(branch)
+        .iter()
+        .map(|&byte| if byte == b'/' { b'-' } else { byte })
+        .collect::<Vec<_>>(),
+    )
+  }
+
+  pub(crate) fn directory_name(project: &OsStr, branch: &OsStr) -> OsString {
+    let mut name = project.to_os_string();
+
+    name.push(".");
+
+    name.push(Self::branch_directory(branch));
+
+    name
+  }
+
+  pub(crate) fn gitdir(path: &Path) -> Option<PathBuf> {
+    let contents = fs::read(path.join(".git")).ok()?;
+
+    let target = contents.strip_prefix(b"gitdir: ")?.trim_ascii_end();
+
+    Some(path.join(bytes::from_bytes(target)))
+  }
+
+  pub(crate) fn name(&self) -> Cow<'_, str> {
+    if let Some(branch) = &self.branch {
+      branch.to_string_lossy()
+    } else if self.bare {
+      Cow::Borrowed("(bare)")
+    } else {
+      Cow::Borrowed("(detached)")
+    }
+  }
+
+  pub(crate) fn parse(porcelain: &[u8]) -> Vec<Self> {
+    porcelain
+      .split(|&byte| byte == 0)
+      .collect::<Vec<_>>()
+      .split(|line| line.is_empty())
+      .filter(|record| !record.is_empty())
+      .filter_map(|record| Self::try_from(record).ok())
+      .collect()
+  }
+
+  pub(crate) fn short_head(&self) -> &str {
+    self
+      .head
+      .as_deref()
+      .map_or("unknown", |head| &head[..head.len().min(7)])
+  }
+
+  pub(crate) fn state(&self) -> String {
+    let describe = |label: &str, reason: &str| {
+      if reason.is_empty() {
+        label.to_string()
+      } else {
+        format!("{label}: {reason}")
+      }
+    };
+
+    self
+      .locked
+      .as_deref()
+      .map(|reason| describe("locked", reason))
+      .into_iter()
+      .chain(
+        self
+          .prunable
+          .as_deref()
+          .map(|reason| describe("prunable", reason)),
+      )
+      .collect::<Vec<_>>()
+      .join(", ")
+  }
+}
+
+impl TryFrom<&[&[u8]]> for Worktree {
   type Error = Error;
 
-  fn try_from(value: &str) -> std::result::Result<Self, Self::Error> {
-    let path = value
-      .lines()
-      .find_map(|line| line.strip_prefix("worktree "))
-      .ok_or_else(|| anyhow!("missing worktree path"))?
-      .to_string();
-
-    let head = value
-      .lines()
-      .find_map(|line| line.strip_prefix("HEAD "))
-      .map_or_else(
-        || "unknown".to_string(),
-        |h| h[..h.len().min(7)].to_string(),
-      );
-
-    let branch = value
-      .lines()
-      .find_map(|line| {
-        line
-          .strip_prefix("branch refs/heads/")
-          .map(str::to_string)
-          .or_else(|| (line == "detached").then(|| "(detached)".to_string()))
-      })
-      .ok_or_else(|| anyhow!("missing branch"))?;
-
-    Ok(Worktree { branch, head, path })
+  fn try_from(lines: &[&[u8]]) -> std::result::Result<Self, Self::Error> {
+    let mut worktree = Worktree::default();
+
+    let mut path = None;
+
+    for line in lines {
+      let (key, rest) = line
+        .iter()
+        .position(|&byte| byte == b' ')
+        .map_or((*line, [].as_slice()), |i| (&line[..i], &line[i + 1..]));
+
+      let text = || String::from_utf8_lossy(rest).into_owned();
+
+      match key {
+        b"bare" => worktree.bare = true,
+        b"branch" => {
+          worktree.branch = Some(bytes::from_bytes(
+            rest.strip_prefix(b"refs/heads/").unwrap_or(rest),
+          ));
+        }
+        b"detached" => worktree.detached = true,
+        b"HEAD" => worktree.head = Some(text()),
+        b"locked" => worktree.locked = Some(text()),
+        b"prunable" => worktree.prunable = Some(text()),
+        b"worktree" => path = Some(PathBuf::from(bytes::from_bytes(rest))),
+        _ => {}
+      }
+    }
+
+    worktree.path = path.ok_or_else(|| anyhow!("missing worktree path"))?;
+
+    if !worktree.bare && worktree.branch.is_none() && !worktree.detached {
+      bail!("missing branch");
+    }
+
+    Ok(worktree)
   }
 }
 
 #[cfg(unix)]
 impl SkimItem for Worktree {
   fn output(&self) -> Cow<'_, str> {
-    Cow::Borrowed(&self.path)
+    self.path.to_string_lossy()
   }
 
   fn text(&self) -> Cow<'_, str> {
-    Cow::Borrowed(&self.branch)
+    let state = self.state();
+
+    if state.is_empty() {
+      self.name()
+    } else {
+      Cow::Owned(format!("{} [{state}]", self.name()))
+    }
   }
 }
 
@@ -55,40 +176,159 @@ mod tests {
   use super::*;
 
   #[test]
-  fn from_block_with_branch() {
+  fn branch_directory() {
     assert_eq!(
-      Worktree::try_from(
-        "worktree /tmp/repo\nHEAD abc123\nbranch refs/heads/main\n"
-      )
-      .unwrap(),
-      Worktree {
-        branch: "main".to_string(),
-        head: "abc123".to_string(),
-        path: "/tmp/repo".to_string(),
-      },
+      Worktree::branch_directory(OsStr::new("feat/my-branch")),
+      "feat-my-branch",
     );
   }
 
   #[test]
-  fn from_block_with_detached() {
+  fn directory_name() {
     assert_eq!(
-      Worktree::try_from("worktree /tmp/repo\nHEAD abc123\ndetached\n")
-        .unwrap(),
-      Worktree {
-        branch: "(detached)".to_string(),
-        head: "abc123".to_string(),
-        path: "/tmp/repo".to_string(),
-      },
+      Worktree::directory_name(
+        OsStr::new("project"),
+        OsStr::new("feat/my-branch")
+      ),
+      "project.feat-my-branch",
     );
   }
 
   #[test]
-  fn from_block_without_branch() {
-    assert!(Worktree::try_from("worktree /tmp/repo\nHEAD abc123\n").is_err());
+  fn parse_bare() {
+    assert_eq!(
+      Worktree::parse(b"worktree /tmp/repo/.bare\0bare\0\0"),
+      vec![Worktree {
+        bare: true,
+        path: PathBuf::from("/tmp/repo/.bare"),
+        ..Worktree::default()
+      }],
+    );
+  }
+
+  #[test]
+  fn parse_branch() {
+    assert_eq!(
+      Worktree::parse(
+        b"worktree /tmp/repo\0HEAD abc123\0branch refs/heads/main\0\0"
+      ),
+      vec![Worktree {
+        branch: Some("main".into()),
+        head: Some("abc123".to_string()),
+        path: PathBuf::from("/tmp/repo"),
+        ..Worktree::default()
+      }],
+    );
+  }
+
+  #[test]
+  fn parse_detached() {
+    assert_eq!(
+      Worktree::parse(b"worktree /tmp/repo\0HEAD abc123\0detached\0\0"),
+      vec![Worktree {
+        detached: true,
+        head: Some("abc123".to_string()),
+        path: PathBuf::from("/tmp/repo"),
+        ..Worktree::default()
+      }],
+    );
+  }
+
+  #[test]
+  fn parse_empty() {
+    assert!(Worktree::parse(b"").is_empty());
+  }
+
+  #[test]
+  fn parse_full_head() {
+    let worktrees = Worktree::parse(
+      b"worktree /tmp/repo\0\
+        HEAD 0123456789abcdef0123456789abcdef01234567\0detached\0\0",
+    );
+
+    assert_eq!(
+      worktrees[0].head.as_deref(),
+      Some("0123456789abcdef0123456789abcdef01234567")
+    );
+
+    assert_eq!(worktrees[0].short_head(), "0123456");
+  }
+
+  #[test]
+  fn parse_lock_and_prune_reasons() {
+    let worktrees = Worktree::parse(
+      b"worktree /tmp/repo\0HEAD abc123\0branch refs/heads/main\0\
+        locked on a usb drive\0prunable gitdir file points to non-existent \
+        location\0\0",
+    );
+
+    assert_eq!(worktrees[0].locked.as_deref(), Some("on a usb drive"));
+
+    assert_eq!(
+      worktrees[0].state(),
+      "locked: on a usb drive, prunable: gitdir file points to non-existent \
+       location",
+    );
+  }
+
+  #[test]
+  fn parse_lock_without_reason() {
+    let worktrees = Worktree::parse(
+      b"worktree /tmp/repo\0HEAD abc123\0branch refs/heads/main\0locked\0\0",
+    );
+
+    assert_eq!(worktrees[0].locked.as_deref(), Some(""));
+
+    assert_eq!(worktrees[0].state(), "locked");
+  }
+
+  #[test]
+  fn parse_multiple() {
+    let worktrees = Worktree::parse(
+      b"worktree /tmp/repo\0HEAD abc123\0branch refs/heads/main\0\0\
+        worktree /tmp/repo.feature\0HEAD def456\0branch refs/heads/feature\0\0",
+    );
+
+    assert_eq!(
+      worktrees
+        .iter()
+        .map(|worktree| worktree.path.clone())
+        .collect::<Vec<_>>(),
+      vec![
+        PathBuf::from("/tmp/repo"),
+        PathBuf::from("/tmp/repo.feature")
+      ],
+    );
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn parse_non_utf8_branch() {
+    use std::os::unix::ffi::OsStrExt;
+
+    assert_eq!(
+      Worktree::parse(
+        b"worktree /tmp/repo\0HEAD abc123\0branch refs/heads/f\xffo\0\0"
+      )[0]
+        .branch
+        .as_deref(),
+      Some(OsStr::from_bytes(b"f\xffo")),
+    );
+  }
+
+  #[test]
+  fn parse_path_with_newline() {
+    assert_eq!(
+      Worktree::parse(
+        b"worktree /tmp/re\npo\0HEAD abc123\0branch refs/heads/main\0\0"
+      )[0]
+        .path,
+      PathBuf::from("/tmp/re\npo"),
+    );
   }
 
   #[test]
-  fn from_empty_block() {
-    assert!(Worktree::try_from("").is_err());
+  fn parse_without_branch() {
+    assert!(Worktree::parse(b"worktree /tmp/repo\0HEAD abc123\0\0").is_empty());
   }
 }
@@ -4,6 +4,7 @@ use {
   pretty_assertions::assert_eq,
   regex::Regex,
   std::{
+    ffi::{OsStr, OsString},
     fs,
     iter::once,
     path::{Path, PathBuf},
@@ -13,11 +14,19 @@ use {
   tempfile::TempDir,
 };
 
+const BACKENDS: &[&str] = &[
+  "git",
+  #[cfg(feature = "gix")]
+  "gix",
+];
+
 type Result<T = (), E = Error> = std::result::Result<T, E>;
 
 #[derive(Debug)]
 struct Test<'a> {
-  arguments: Vec<String>,
+  arguments: Vec<OsString>,
+  backend: Option<&'a str>,
+  environment: Vec<(&'a str, &'a str)>,
   exists: Vec<&'a str>,
   expected_status: i32,
   expected_stderr: String,
@@ -27,30 +36,68 @@ struct Test<'a> {
 }
 
 impl<'a> Test<'a> {
-  fn argument(self, argument: &str) -> Self {
+  fn argument(self, argument: impl AsRef<OsStr>) -> Self {
     Self {
       arguments: self
         .arguments
         .into_iter()
-        .chain(once(argument.to_owned()))
+        .chain(once(argument.as_ref().to_owned()))
         .collect(),
       ..self
     }
   }
 
-  fn command(&self, arguments: &[String]) -> Result<Output> {
+  fn backend(self, backend: &'a str) -> Self {
+    Self {
+      backend: Some(backend),
+      ..self
+    }
+  }
+
+  fn command(&self, arguments: &[impl AsRef<OsStr>]) -> Result<Output> {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_wt"));
+
+    match self.backend {
+      Some(backend) => command.env("WT_BACKEND", backend),
+      None => command.env_remove("WT_BACKEND"),
+    };
+
     Ok(
-      Command::new(env!("CARGO_BIN_EXE_wt"))
+      command
         .args(arguments)
         .current_dir(&self.workdir)
         .env("NO_COLOR", "1")
         .env("GIT_CONFIG_GLOBAL", "/dev/null")
         .env("GIT_CONFIG_SYSTEM", "/dev/null")
         .env("GIT_CEILING_DIRECTORIES", self.tempdir.path())
+        .env("HOME", self.tempdir.path())
+        .env("XDG_CONFIG_HOME", self.tempdir.path().join(".config"))
+        .envs(self.environment.iter().copied())
         .output()?,
     )
   }
 
+  fn commit(self, directory: &str, file: &str, contents: &str) -> Self {
+    let directory = self.tempdir.path().join(directory);
+
+    fs::write(directory.join(file), contents).unwrap();
+
+    Self::git(&directory, &["add", file]);
+    Self::git(&directory, &["commit", "-m", file]);
+
+    self
+  }
+
+  fn config(self, config: &str) -> Self {
+    let directory = self.tempdir.path().join(".config/wt");
+
+    fs::create_dir_all(&directory).unwrap();
+
+    fs::write(directory.join("config.toml"), config).unwrap();
+
+    self
+  }
+
   fn directory(self, directory: impl AsRef<Path>) -> Self {
     Self {
       workdir: self.tempdir.path().join(directory),
@@ -58,6 +105,18 @@ impl<'a> Test<'a> {
     }
   }
 
+  #[cfg(unix)]
+  fn environment(self, key: &'a str, value: &'a str) -> Self {
+    Self {
+      environment: self
+        .environment
+        .into_iter()
+        .chain(once((key, value)))
+        .collect(),
+      ..self
+    }
+  }
+
   fn exists(self, paths: &[&'a str]) -> Self {
     Self {
       exists: self
@@ -107,6 +166,30 @@ impl<'a> Test<'a> {
     );
   }
 
+  #[cfg(unix)]
+  fn journal(self, pid: u32, fields: &[(&str, &str)]) -> Self {
+    let root = self.tempdir.path().canonicalize().unwrap();
+
+    let directory = root.join("project/.git/wt/journal");
+
+    fs::create_dir_all(&directory).unwrap();
+
+    let mut contents = String::new();
+
+    for (key, value) in fields {
+      contents.push_str(key);
+      contents.push(' ');
+      contents.push_str(&value.replace("[ROOT]", root.to_str().unwrap()));
+      contents.push('\0');
+    }
+
+    contents.push('\0');
+
+    fs::write(directory.join(pid.to_string()), contents).unwrap();
+
+    self
+  }
+
   fn new(name: &str) -> Result<Self> {
     let tempdir = TempDir::new()?;
 
@@ -127,6 +210,8 @@ impl<'a> Test<'a> {
 
     Ok(Self {
       arguments: Vec::new(),
+      backend: None,
+      environment: Vec::new(),
       exists: Vec::new(),
       expected_status: 0,
       expected_stderr: String::new(),
@@ -150,7 +235,7 @@ impl<'a> Test<'a> {
 
     let stderr = hash_regex
       .replace_all(
-        &str::from_utf8(&output.stderr)?
+        &String::from_utf8_lossy(&output.stderr)
           .replace('\\', "/")
           .replace(&root, "[ROOT]"),
         "[HASH]",
@@ -159,7 +244,7 @@ impl<'a> Test<'a> {
 
     let stdout = hash_regex
       .replace_all(
-        &str::from_utf8(&output.stdout)?
+        &String::from_utf8_lossy(&output.stdout)
           .replace('\\', "/")
           .replace(&root, "[ROOT]"),
         "[HASH]",
@@ -208,6 +293,118 @@ impl<'a> Test<'a> {
     self
   }
 
+  #[cfg(unix)]
+  fn submodule(self, path: &str) -> Self {
+    let root = self.tempdir.path().canonicalize().unwrap();
+
+    let library = root.join("library");
+
+    Self::git(&root, &["init", "-b", "main", "library"]);
+    Self::git(&library, &["config", "user.email", "test@test.com"]);
+    Self::git(&library, &["config", "user.name", "Test"]);
+
+    let test = self.commit("library", "lib.txt", "lib");
+
+    let project = root.join("project");
+
+    Self::git(
+      &project,
+      &[
+        "-c",
+        "protocol.file.allow=always",
+        "submodule",
+        "add",
+        library.to_str().unwrap(),
+        path,
+      ],
+    );
+    Self::git(&project, &["commit", "-m", "Add submodule"]);
+
+    test
+      .environment("GIT_CONFIG_COUNT", "1")
+      .environment("GIT_CONFIG_KEY_0", "protocol.file.allow")
+      .environment("GIT_CONFIG_VALUE_0", "always")
+  }
+
+  #[cfg(unix)]
+  fn trash(self, branch: &str, age: u64) -> Self {
+    let root = self.tempdir.path().canonicalize().unwrap();
+
+    let project = root.join("project");
+
+    let path = root.join(format!("project.{branch}"));
+
+    let head = Command::new("git")
+      .args(["rev-parse", "HEAD"])
+      .current_dir(&path)
+      .output()
+      .unwrap()
+      .stdout;
+
+    let head = str::from_utf8(&head).unwrap().trim();
+
+    let removed = std::time::SystemTime::now()
+      .duration_since(std::time::UNIX_EPOCH)
+      .unwrap()
+      .as_secs()
+      - age;
+
+    let id = format!("{removed}-1-0");
+
+    let directory = project.join(".git/wt/trash").join(&id);
+
+    fs::create_dir_all(&directory).unwrap();
+
+    fs::copy(
+      project.join(format!(".git/worktrees/project.{branch}/index")),
+      directory.join("index"),
+    )
+    .unwrap();
+
+    let mut contents = String::new();
+
+    for (key, value) in [
+      ("path", path.to_str().unwrap()),
+      ("trash", directory.join("worktree").to_str().unwrap()),
+      ("branch", branch),
+      ("head", head),
+    ] {
+      contents.push_str(key);
+      contents.push(' ');
+      contents.push_str(value);
+      contents.push('\0');
+    }
+
+    contents.push('\0');
+
+    fs::write(directory.join("entry"), contents).unwrap();
+
+    fs::rename(&path, directory.join("worktree")).unwrap();
+
+    Self::git(
+      &project,
+      &["update-ref", &format!("refs/wt-trash/{id}"), head],
+    );
+    Self::git(&project, &["worktree", "prune"]);
+    Self::git(&project, &["branch", "-D", branch]);
+
+    self
+  }
+
+  #[cfg(unix)]
+  fn url(&self, name: &str) -> String {
+    format!(
+      "file://{}",
+      self
+        .tempdir
+        .path()
+        .canonicalize()
+        .unwrap()
+        .join(name)
+        .display()
+    )
+  }
+
   fn without_git() -> Result<Self> {
     let tempdir = TempDir::new()?;
 
@@ -215,6 +412,8 @@ impl<'a> Test<'a> {
 
     Ok(Self {
       arguments: Vec::new(),
+      backend: None,
+      environment: Vec::new(),
       exists: Vec::new(),
       expected_status: 0,
       expected_stderr: String::new(),
@@ -225,182 +424,2815 @@ impl<'a> Test<'a> {
   }
 }
 
-#[cfg(unix)]
 #[test]
-fn convert_no_branches() -> Result {
+fn backend_bare_repository() -> Result {
+  for backend in BACKENDS {
+    let test = Test::new("project")?.backend(backend);
+
+    Test::git(
+      test.tempdir.path(),
+      &["clone", "--bare", "project", "bare.git"],
+    );
+
+    Test::git(
+      &test.tempdir.path().join("bare.git"),
+      &["worktree", "add", "../checkout", "main"],
+    );
+
+    test
+      .directory("checkout")
+      .argument("list")
+      .argument("--template")
+      .argument("{branch}\t{head}\t{subject}\t{path}")
+      .expected_stdout(indoc! {
+        "
+        (bare)\tunknown\tInitial commit\t[ROOT]/bare.git
+        main\t[HASH]\tInitial commit\t[ROOT]/checkout
+        "
+      })
+      .run()?;
+  }
+
+  Ok(())
+}
+
+#[cfg(not(feature = "gix"))]
+#[test]
+fn backend_gix_without_feature() -> Result {
   Test::new("project")?
-    .argument("convert")
+    .backend("gix")
+    .argument("list")
     .expected_status(1)
-    .expected_stderr("error: no branches without worktrees\n")
+    .expected_stderr(
+      "error: invalid `WT_BACKEND`\n       └─ `wt` was built without the \
+       `gix` feature\n",
+    )
     .run()
 }
 
 #[test]
-fn create() -> Result {
-  Test::new("project")?
-    .argument("create")
-    .argument("feature")
-    .exists(&["project.feature"])
-    .expected_stderr("created worktree feature at project.feature\n")
-    .expected_stdout("[ROOT]/project.feature\n")
-    .run()
+fn backend_list() -> Result {
+  for backend in BACKENDS {
+    let test = Test::new("project")?.backend(backend);
+
+    fs::write(test.workdir.join("file.txt"), "hello\n")?;
+    fs::write(test.workdir.join("gone.txt"), "one\ntwo\n")?;
+
+    Test::git(&test.workdir, &["add", "file.txt", "gone.txt"]);
+    Test::git(&test.workdir, &["commit", "-m", "add files"]);
+
+    let test = test
+      .setup(&["create", "feature"])
+      .setup(&["create", "locked"])
+      .setup(&["create", "missing"]);
+
+    let feature = test.tempdir.path().join("project.feature");
+
+    fs::write(feature.join("file.txt"), "hello\nworld\n")?;
+    fs::remove_file(feature.join("gone.txt"))?;
+    fs::write(feature.join("untracked.txt"), "ignored\n")?;
+
+    Test::git(
+      &test.workdir,
+      &["worktree", "lock", "--reason", "usb", "../project.locked"],
+    );
+
+    fs::remove_dir_all(test.tempdir.path().join("project.missing"))?;
+
+    test
+      .argument("list")
+      .argument("--sort")
+      .argument("name")
+      .argument("--template")
+      .argument("{branch}\t{diff}\t{subject}\t{state}\t{path}")
+      .expected_stdout(indoc! {
+        "
+        feature\t+1/-2\tadd files\t\t[ROOT]/project.feature
+        locked\t+0/-0\tadd files\tlocked: usb\t[ROOT]/project.locked
+        main\t+0/-0\tadd files\t\t[ROOT]/project
+        missing\t+0/-0\t\tprunable: gitdir file points to non-existent location\t[ROOT]/project.missing
+        "
+      })
+      .run()?;
+  }
+
+  Ok(())
 }
 
 #[test]
-fn create_duplicate_branch() -> Result {
+fn backend_unknown() -> Result {
   Test::new("project")?
-    .setup(&["create", "feature"])
-    .argument("create")
-    .argument("feature")
-    .exists(&["project.feature"])
+    .backend("nope")
+    .argument("list")
     .expected_status(1)
+    .expected_stderr(
+      "error: invalid `WT_BACKEND`\n       └─ unknown backend `nope`, \
+       expected `git` or `gix`\n",
+    )
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn clone() -> Result {
+  let test = Test::new("upstream")?;
+
+  let url = test.url("upstream");
+
+  test
+    .directory("")
+    .argument("clone")
+    .argument(&url)
+    .argument("project")
     .expected_stderr(indoc! {
       "
-      error: failed to create worktree `feature`: Preparing worktree (new branch 'feature')
-      fatal: a branch named 'feature' already exists
+      cloned file://[ROOT]/upstream into [ROOT]/project
+      created worktree main at [ROOT]/project/main
       "
     })
+    .expected_stdout("[ROOT]/project/main\n")
+    .exists(&["project/.bare/HEAD", "project/.git", "project/main/.git"])
     .run()
 }
 
+#[cfg(unix)]
+#[test]
+fn clone_configures_layout() -> Result {
+  let test = Test::new("upstream")?;
+
+  Test::git(&test.workdir, &["branch", "feature"]);
+
+  fs::create_dir(test.tempdir.path().join("work"))?;
+
+  let test = test.directory("work");
+
+  let output = test.command(&["clone", &test.url("upstream")])?;
+
+  assert!(
+    output.status.success(),
+    "{}",
+    String::from_utf8_lossy(&output.stderr)
+  );
+
+  let project = test.tempdir.path().join("work/upstream");
+
+  assert_eq!(
+    fs::read_to_string(project.join(".git"))?,
+    "gitdir: ./.bare\n"
+  );
+
+  let git = |arguments: &[&str]| {
+    let output = Command::new("git")
+      .args(arguments)
+      .current_dir(project.join("main"))
+      .env("GIT_CONFIG_GLOBAL", "/dev/null")
+      .env("GIT_CONFIG_SYSTEM", "/dev/null")
+      .output()
+      .unwrap();
+
+    String::from_utf8(output.stdout).unwrap()
+  };
+
+  assert_eq!(
+    git(&["config", "remote.origin.fetch"]),
+    "+refs/heads/*:refs/remotes/origin/*\n",
+  );
+
+  assert_eq!(
+    git(&["rev-parse", "--abbrev-ref", "main@{upstream}"]),
+    "origin/main\n",
+  );
+
+  assert_eq!(
+    git(&["for-each-ref", "--format=%(refname)", "refs/remotes/"]),
+    "refs/remotes/origin/feature\nrefs/remotes/origin/main\n",
+  );
+
+  Ok(())
+}
+
 #[test]
-fn create_from_worktree_uses_head_path() -> Result {
+fn clone_into_non_empty_directory() -> Result {
   Test::new("project")?
-    .setup(&["create", "feature"])
-    .directory("project.feature")
-    .argument("create")
-    .argument("bar")
-    .exists(&["project.bar"])
-    .expected_stderr("created worktree bar at project.bar\n")
-    .expected_stdout("[ROOT]/project.bar\n")
+    .directory("")
+    .argument("clone")
+    .argument("upstream")
+    .argument("project")
+    .expected_status(1)
+    .expected_stderr(
+      "error: destination `[ROOT]/project` already exists and is not empty\n",
+    )
     .run()
 }
 
+#[cfg(unix)]
 #[test]
+fn config_edit() -> Result {
+  let test = Test::new("project")?
+    .config("[trash]\nenabled = false\n")
+    .environment("EDITOR", "cp ../edited.toml");
 
-fn create_in_subdirectory() -> Result {
-  let test = Test::new("project")?;
+  fs::write(
+    test.tempdir.path().join("edited.toml"),
+    "# trash everything\n[trash]\nenabled = true\n",
+  )?;
 
-  fs::create_dir(test.workdir.join("subdir"))?;
+  let output = test.command(&["config", "edit"])?;
 
-  test
-    .directory("project/subdir")
-    .argument("create")
-    .argument("feature")
-    .exists(&["project.feature"])
-    .expected_stderr("created worktree feature at project.feature\n")
-    .expected_stdout("[ROOT]/project.feature\n")
-    .run()
-}
+  assert!(output.status.success());
 
-#[test]
-fn create_outside_git_repo() -> Result {
-  Test::without_git()?
-    .argument("create")
-    .argument("feature")
-    .expected_status(1)
-    .expected_stderr("error: failed to get project name from ``\n")
-    .run()
+  assert_eq!(
+    fs::read_to_string(test.tempdir.path().join(".config/wt/config.toml"))?,
+    "# trash everything\n[trash]\nenabled = true\n",
+  );
+
+  Ok(())
 }
 
+#[cfg(unix)]
 #[test]
-fn create_slash_in_branch_name() -> Result {
-  Test::new("project")?
-    .argument("create")
-    .argument("feat/my-branch")
-    .exists(&["project.feat-my-branch"])
-    .expected_stderr(
-      "created worktree feat/my-branch \
-       at project.feat-my-branch\n",
-    )
-    .expected_stdout("[ROOT]/project.feat-my-branch\n")
-    .run()
+fn config_edit_invalid() -> Result {
+  let test = Test::new("project")?
+    .config("[trash]\nenabled = false\n")
+    .environment("EDITOR", "cp ../edited.toml");
+
+  fs::write(
+    test.tempdir.path().join("edited.toml"),
+    "[trash]\nenabld = true\n",
+  )?;
+
+  let output = test.command(&["config", "edit"])?;
+
+  assert!(!output.status.success());
+
+  let stderr = str::from_utf8(&output.stderr)?;
+
+  assert!(stderr.starts_with("error: config not saved"), "{stderr}");
+
+  assert!(stderr.contains(":2:1: unknown field `enabld`"), "{stderr}");
+
+  let kept = Regex::new("your changes are in `([^`]+)`")?
+    .captures(stderr)
+    .map(|captures| PathBuf::from(&captures[1]))
+    .unwrap();
+
+  assert_eq!(fs::read_to_string(&kept)?, "[trash]\nenabld = true\n");
+
+  fs::remove_file(kept)?;
+
+  assert_eq!(
+    fs::read_to_string(test.tempdir.path().join(".config/wt/config.toml"))?,
+    "[trash]\nenabled = false\n",
+  );
+
+  Ok(())
 }
 
 #[test]
-fn init_zsh() -> Result {
+fn config_get() -> Result {
   Test::new("project")?
-    .argument("init")
-    .argument("zsh")
-    .expected_stdout(include_str!("../src/subcommand/init.zsh"))
+    .config("[trash]\nenabled = true\n")
+    .argument("config")
+    .argument("get")
+    .argument("trash.enabled")
+    .expected_stdout("true\n")
     .run()
 }
 
 #[test]
-fn list() -> Result {
+fn config_get_default() -> Result {
   Test::new("project")?
-    .argument("list")
-    .expected_stdout("* main  [HASH]  +0/-0  [ROOT]/project\n")
+    .argument("config")
+    .argument("get")
+    .argument("trash.retention_days")
+    .expected_stdout("7\n")
     .run()
 }
 
 #[test]
-fn list_after_create() -> Result {
+fn config_get_unset() -> Result {
   Test::new("project")?
-    .setup(&["create", "feature"])
-    .argument("list")
-    .expected_stdout(indoc! {
-      "
-      * main     [HASH]  +0/-0  [ROOT]/project
-        feature  [HASH]  +0/-0  [ROOT]/project.feature
-      "
-    })
+    .argument("config")
+    .argument("get")
+    .argument("sync.base")
+    .expected_status(1)
+    .expected_stderr("error: config key `sync.base` is not set\n")
     .run()
 }
 
+#[cfg(unix)]
 #[test]
-fn list_outside_git_repo() -> Result {
-  Test::without_git()?
+fn config_invalid_type() -> Result {
+  Test::new("project")?
+    .config("[trash]\nretention_days = \"week\"\n")
     .argument("list")
     .expected_status(1)
-    .expected_stderr("error: failed to list worktrees\n")
+    .expected_stderr(indoc! {
+      r#"
+      error: invalid config
+             └─ [ROOT]/.config/wt/config.toml:2:18: invalid type: string "week", expected u64
+      "#
+    })
     .run()
 }
 
 #[test]
-fn list_with_changes() -> Result {
+fn config_is_not_written_on_read() -> Result {
   let test = Test::new("project")?;
 
-  fs::write(test.workdir.join("file.txt"), "hello\n")?;
+  let output = test.command(&["list", "--template", "{branch}"])?;
 
-  Test::git(&test.workdir, &["add", "file.txt"]);
-  Test::git(&test.workdir, &["commit", "-m", "add file.txt"]);
+  assert!(output.status.success());
 
-  let test = test.setup(&["create", "feature"]);
+  assert!(!test.tempdir.path().join(".config/wt").exists());
 
-  fs::write(
-    test.tempdir.path().join("project.feature").join("file.txt"),
-    "hello\nworld\n",
-  )?;
+  Ok(())
+}
 
-  test
-    .argument("list")
-    .expected_stdout(indoc! {
-      "
-      * main     [HASH]  +0/-0  [ROOT]/project
-        feature  [HASH]  +1/-0  [ROOT]/project.feature
-      "
-    })
+#[cfg(unix)]
+#[test]
+fn config_path() -> Result {
+  Test::new("project")?
+    .argument("config")
+    .argument("path")
+    .expected_stdout("[ROOT]/.config/wt/config.toml\n")
     .run()
 }
 
 #[cfg(unix)]
 #[test]
-fn remove_no_worktrees() -> Result {
+fn config_path_repo() -> Result {
   Test::new("project")?
-    .argument("remove")
-    .expected_status(1)
-    .expected_stderr("error: no worktrees to remove\n")
+    .argument("config")
+    .argument("path")
+    .argument("--repo")
+    .expected_stdout("[ROOT]/project/.git/wt/config.toml\n")
     .run()
 }
 
 #[cfg(unix)]
 #[test]
-fn switch_no_worktrees() -> Result {
-  Test::without_git()?
-    .argument("switch")
+fn config_set_invalid() -> Result {
+  Test::new("project")?
+    .config("[trash]\nenabled = false\n")
+    .argument("config")
+    .argument("set")
+    .argument("trash.retention_days")
+    .argument("week")
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      r#"
+      error: failed to set `trash.retention_days`
+             ├─ invalid config
+             └─ [ROOT]/.config/wt/config.toml:3:18: invalid type: string "week", expected u64
+      "#
+    })
+    .run()
+}
+
+#[test]
+fn config_set_keeps_comments() -> Result {
+  let test = Test::new("project")?
+    .config("# settings\n[trash]\nenabled = false # for now\n");
+
+  let output = test.command(&["config", "set", "trash.enabled", "true"])?;
+
+  assert!(output.status.success());
+
+  let output = test.command(&["config", "set", "sync.base", "develop"])?;
+
+  assert!(output.status.success());
+
+  assert_eq!(
+    fs::read_to_string(test.tempdir.path().join(".config/wt/config.toml"))?,
+    "# settings\n[trash]\nenabled = true # for now\n\n[sync]\nbase = \"develop\"\n",
+  );
+
+  Ok(())
+}
+
+#[test]
+fn config_set_repo() -> Result {
+  let test = Test::new("project")?.config("[trash]\nenabled = false\n");
+
+  let output =
+    test.command(&["config", "set", "--repo", "trash.enabled", "true"])?;
+
+  assert!(output.status.success());
+
+  assert_eq!(
+    fs::read_to_string(
+      test.tempdir.path().join("project/.git/wt/config.toml")
+    )?,
+    "[trash]\nenabled = true\n",
+  );
+
+  let output = test.command(&["config", "get", "trash.enabled"])?;
+
+  assert_eq!(str::from_utf8(&output.stdout)?, "true\n");
+
+  Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn config_unknown_key() -> Result {
+  Test::new("project")?
+    .config("[list]\ncolums = [\"branch\"]\n")
+    .argument("list")
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      "
+      error: invalid config
+             └─ [ROOT]/.config/wt/config.toml:2:1: unknown field `colums`, expected `columns` or `path`
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn convert_no_branches() -> Result {
+  Test::new("project")?
+    .argument("convert")
+    .expected_status(1)
+    .expected_stderr("error: no branches without worktrees\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn convert_outside_git_repo() -> Result {
+  Test::without_git()?
+    .argument("convert")
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      "
+      error: not a git repository
+             └─ fatal: not a git repository (or any of the parent directories): .git
+      "
+    })
+    .run()
+}
+
+#[test]
+fn convert_repo() -> Result {
+  let test = Test::new("project")?;
+
+  fs::write(test.workdir.join("file.txt"), "hello\n")?;
+
+  Test::git(&test.workdir, &["add", "file.txt"]);
+  Test::git(&test.workdir, &["commit", "-m", "add file.txt"]);
+
+  test
+    .argument("convert-repo")
+    .expected_stderr("converted [ROOT]/project into [ROOT]/project/main\n")
+    .expected_stdout("[ROOT]/project/main\n")
+    .exists(&[
+      "project/.bare/HEAD",
+      "project/.git",
+      "project/main/file.txt",
+    ])
+    .run()
+}
+
+#[test]
+fn convert_repo_already_bare() -> Result {
+  let test = Test::new("project")?;
+
+  Test::git(
+    test.tempdir.path(),
+    &["clone", "--bare", "project", "bare.git"],
+  );
+
+  test
+    .directory("bare.git")
+    .argument("convert-repo")
+    .expected_status(1)
+    .expected_stderr("error: repository is already bare\n")
+    .run()
+}
+
+#[test]
+fn convert_repo_during_merge() -> Result {
+  let test = Test::new("project")?;
+
+  fs::write(test.workdir.join(".git/MERGE_HEAD"), "")?;
+
+  test
+    .argument("convert-repo")
+    .expected_status(1)
+    .expected_stderr(
+      "error: refusing to convert while a merge is in progress\n",
+    )
+    .run()
+}
+
+#[test]
+fn convert_repo_keeps_state() -> Result {
+  let test = Test::new("project")?;
+
+  fs::write(test.workdir.join("staged.txt"), "one\n")?;
+  fs::write(test.workdir.join("unstaged.txt"), "one\n")?;
+
+  Test::git(&test.workdir, &["add", "."]);
+  Test::git(&test.workdir, &["commit", "-m", "add files"]);
+
+  fs::write(test.workdir.join("staged.txt"), "stash\n")?;
+
+  Test::git(&test.workdir, &["stash"]);
+
+  fs::write(test.workdir.join("staged.txt"), "two\n")?;
+  fs::write(test.workdir.join("unstaged.txt"), "two\n")?;
+  fs::write(test.workdir.join("untracked.txt"), "two\n")?;
+
+  Test::git(&test.workdir, &["add", "staged.txt"]);
+
+  let test = test.setup(&["create", "feature"]);
+
+  let output = test.command(&["convert-repo"])?;
+
+  assert!(
+    output.status.success(),
+    "{}",
+    String::from_utf8_lossy(&output.stderr)
+  );
+
+  let git = |directory: &str, arguments: &[&str]| {
+    let output = Command::new("git")
+      .args(arguments)
+      .current_dir(test.tempdir.path().join(directory))
+      .env("GIT_CONFIG_GLOBAL", "/dev/null")
+      .env("GIT_CONFIG_SYSTEM", "/dev/null")
+      .output()
+      .unwrap();
+
+    assert!(
+      output.status.success(),
+      "{}",
+      String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+  };
+
+  assert_eq!(
+    git("project/main", &["status", "--short"]),
+    "M  staged.txt\n M unstaged.txt\n?? untracked.txt\n",
+  );
+
+  assert_eq!(
+    git("project/main", &["stash", "list", "--format=%gd"]),
+    "stash@{0}\n",
+  );
+
+  assert_eq!(git("project", &["config", "core.bare"]), "true\n",);
+
+  assert_eq!(git("project.feature", &["status", "--short"]), "");
+
+  assert_eq!(
+    git("project.feature", &["rev-parse", "--abbrev-ref", "HEAD"]),
+    "feature\n",
+  );
+
+  Ok(())
+}
+
+#[test]
+fn convert_repo_rolls_back_on_failure() -> Result {
+  let test = Test::new("project")?.backend("git");
+
+  fs::write(test.workdir.join("file.txt"), "hello\n")?;
+
+  Test::git(&test.workdir, &["add", "file.txt"]);
+  Test::git(&test.workdir, &["commit", "-m", "add file.txt"]);
+
+  fs::write(test.workdir.join("untracked.txt"), "hello\n")?;
+
+  fs::write(test.workdir.join(".git/worktrees"), "")?;
+
+  let output = test.command(&["convert-repo"])?;
+
+  assert!(!output.status.success());
+
+  let stderr = str::from_utf8(&output.stderr)?;
+
+  assert!(
+    stderr.starts_with("error: failed to convert `")
+      && stderr.contains("changes were rolled back")
+      && stderr.contains("failed to register worktree"),
+    "{stderr}",
+  );
+
+  let mut entries = fs::read_dir(&test.workdir)?
+    .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+    .collect::<Result<Vec<_>>>()?;
+
+  entries.sort();
+
+  assert_eq!(entries, [".git", "file.txt", "untracked.txt"]);
+
+  assert!(test.workdir.join(".git").is_dir());
+
+  fs::remove_file(test.workdir.join(".git/worktrees"))?;
+
+  let status = Command::new("git")
+    .args(["status", "--short"])
+    .current_dir(&test.workdir)
+    .output()?;
+
+  assert_eq!(str::from_utf8(&status.stdout)?, "?? untracked.txt\n");
+
+  let output = test.command(&["convert-repo"])?;
+
+  assert!(output.status.success());
+
+  Ok(())
+}
+
+#[test]
+fn create() -> Result {
+  Test::new("project")?
+    .argument("create")
+    .argument("feature")
+    .exists(&["project.feature"])
+    .expected_stderr("created worktree feature at project.feature\n")
+    .expected_stdout("[ROOT]/project.feature\n")
+    .run()
+}
+
+#[test]
+fn create_bare_repository() -> Result {
+  let test = Test::new("project")?;
+
+  Test::git(
+    test.tempdir.path(),
+    &["clone", "--bare", "project", "bare.git"],
+  );
+
+  Test::git(
+    &test.tempdir.path().join("bare.git"),
+    &["worktree", "add", "../checkout", "main"],
+  );
+
+  test
+    .directory("checkout")
+    .argument("create")
+    .argument("feat/thing")
+    .expected_stderr("created worktree feat/thing at feat-thing\n")
+    .expected_stdout("[ROOT]/feat-thing\n")
+    .exists(&["feat-thing"])
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn create_clones_directories() -> Result {
+  let test = Test::new("project")?
+    .config("[create]\nclone_dirs = [\"target\", \"missing\"]\n")
+    .commit("project", ".gitignore", "target\n");
+
+  let target = test.tempdir.path().join("project/target");
+
+  fs::create_dir_all(target.join("debug/deps"))?;
+  fs::write(target.join("debug/app"), "binary")?;
+  fs::write(target.join("debug/deps/lib.rlib"), "rlib")?;
+
+  std::os::unix::fs::symlink("debug", target.join("latest"))?;
+
+  let output = test.command(&["create", "feature"])?;
+
+  let stderr = str::from_utf8(&output.stderr)?;
+
+  assert!(
+    Regex::new(
+      r"^created worktree feature at project.feature\ncloned target in \d+\.\d{2}s, sharing 10 B via (reflinks|hardlinks), recreating 1 symlink\n$"
+    )?
+    .is_match(stderr),
+    "unexpected stderr: {stderr}",
+  );
+
+  let worktree = test.tempdir.path().join("project.feature/target");
+
+  assert_eq!(fs::read_to_string(worktree.join("debug/app"))?, "binary");
+  assert_eq!(
+    fs::read_to_string(worktree.join("debug/deps/lib.rlib"))?,
+    "rlib"
+  );
+  assert_eq!(fs::read_link(worktree.join("latest"))?, Path::new("debug"));
+  assert!(!test.tempdir.path().join("project.feature/missing").exists());
+
+  Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn create_copies_and_links_ignored_files() -> Result {
+  let test = Test::new("project")?
+    .config(indoc! {
+      r#"
+      [[create.copy]]
+      pattern = ".env*"
+
+      [[create.link]]
+      pattern = "node_modules"
+      "#
+    })
+    .commit("project", ".gitignore", ".env*\nnode_modules\nsecret\n");
+
+  let project = test.tempdir.path().join("project");
+
+  fs::write(project.join(".env"), "KEY=value\n")?;
+  fs::write(project.join(".env.local"), "LOCAL=1\n")?;
+  fs::write(project.join("secret"), "hunter2\n")?;
+  fs::create_dir_all(project.join("node_modules/pkg"))?;
+  fs::write(project.join("node_modules/pkg/index.js"), "")?;
+
+  let output = test.command(&["create", "feature"])?;
+
+  assert_eq!(
+    str::from_utf8(&output.stderr)?,
+    indoc! {
+      "
+      created worktree feature at project.feature
+      copied .env
+      copied .env.local
+      linked node_modules
+      "
+    }
+  );
+
+  let worktree = test.tempdir.path().join("project.feature");
+
+  assert_eq!(fs::read_to_string(worktree.join(".env"))?, "KEY=value\n");
+  assert_eq!(
+    fs::read_to_string(worktree.join(".env.local"))?,
+    "LOCAL=1\n"
+  );
+  assert!(!worktree.join("secret").exists());
+  assert_eq!(
+    fs::read_link(worktree.join("node_modules"))?,
+    project.canonicalize()?.join("node_modules")
+  );
+
+  Ok(())
+}
+
+#[test]
+fn create_duplicate_branch() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .argument("create")
+    .argument("feature")
+    .exists(&["project.feature"])
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      "
+      error: failed to create worktree `feature`
+             └─ Preparing worktree (new branch 'feature')
+                fatal: a branch named 'feature' already exists
+      "
+    })
+    .run()
+}
+
+#[test]
+fn create_from_worktree_uses_head_path() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .directory("project.feature")
+    .argument("create")
+    .argument("bar")
+    .exists(&["project.bar"])
+    .expected_stderr("created worktree bar at project.bar\n")
+    .expected_stdout("[ROOT]/project.bar\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn create_in_cloned_layout() -> Result {
+  let test = Test::new("upstream")?.directory("");
+
+  let url = test.url("upstream");
+
+  test
+    .setup(&["clone", &url, "project"])
+    .directory("project/main")
+    .argument("create")
+    .argument("feature")
+    .expected_stderr("created worktree feature at feature\n")
+    .expected_stdout("[ROOT]/project/feature\n")
+    .exists(&["project/feature"])
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn create_in_cloned_layout_root() -> Result {
+  let test = Test::new("upstream")?.directory("");
+
+  let url = test.url("upstream");
+
+  test
+    .setup(&["clone", &url, "project"])
+    .directory("project")
+    .argument("create")
+    .argument("feature")
+    .expected_stderr("created worktree feature at feature\n")
+    .expected_stdout("[ROOT]/project/feature\n")
+    .exists(&["project/feature"])
+    .run()
+}
+
+#[test]
+
+fn create_in_subdirectory() -> Result {
+  let test = Test::new("project")?;
+
+  fs::create_dir(test.workdir.join("subdir"))?;
+
+  test
+    .directory("project/subdir")
+    .argument("create")
+    .argument("feature")
+    .exists(&["project.feature"])
+    .expected_stderr("created worktree feature at project.feature\n")
+    .expected_stdout("[ROOT]/project.feature\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn create_initializes_submodules() -> Result {
+  let test = Test::new("project")?.submodule("vendor/lib");
+
+  let root = test.tempdir.path().canonicalize()?;
+
+  let output = test.command(&["create", "feature"])?;
+
+  let stderr = str::from_utf8(&output.stderr)?;
+
+  assert!(
+    Regex::new(
+      r"^created worktree feature at project.feature\ninitialized submodule vendor/lib \(1/1\) in \d+\.\d{2}s, reusing existing objects\n$"
+    )?
+    .is_match(stderr),
+    "unexpected stderr: {stderr}",
+  );
+
+  assert_eq!(
+    fs::read_to_string(root.join("project.feature/vendor/lib/lib.txt"))?,
+    "lib"
+  );
+
+  Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn create_non_utf8_branch() -> Result {
+  use std::os::unix::ffi::OsStrExt;
+
+  let test = Test::new("project")?;
+
+  let output =
+    test.command(&[OsStr::new("create"), OsStr::from_bytes(b"f\xffo")])?;
+
+  assert!(
+    output.status.success(),
+    "{}",
+    String::from_utf8_lossy(&output.stderr)
+  );
+
+  let path = test
+    .tempdir
+    .path()
+    .canonicalize()?
+    .join(OsStr::from_bytes(b"project.f\xffo"));
+
+  assert!(path.is_dir());
+
+  assert_eq!(output.stdout, [path.as_os_str().as_bytes(), b"\n"].concat());
+
+  Ok(())
+}
+
+#[test]
+fn create_outside_git_repo() -> Result {
+  Test::without_git()?
+    .argument("create")
+    .argument("feature")
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      "
+      error: not a git repository
+             └─ fatal: not a git repository (or any of the parent directories): .git
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn create_path_with_newline() -> Result {
+  Test::new("pro\nject")?
+    .argument("create")
+    .argument("feature")
+    .exists(&["pro\nject.feature"])
+    .expected_stderr("created worktree feature at pro\nject.feature\n")
+    .expected_stdout("[ROOT]/pro\nject.feature\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn create_skips_submodules_when_disabled() -> Result {
+  let test = Test::new("project")?
+    .config("[create]\nsubmodules = false\n")
+    .submodule("vendor/lib");
+
+  test
+    .argument("create")
+    .argument("feature")
+    .expected_stderr("created worktree feature at project.feature\n")
+    .expected_stdout("[ROOT]/project.feature\n")
+    .run()
+}
+
+#[test]
+fn create_slash_in_branch_name() -> Result {
+  Test::new("project")?
+    .argument("create")
+    .argument("feat/my-branch")
+    .exists(&["project.feat-my-branch"])
+    .expected_stderr(
+      "created worktree feat/my-branch \
+       at project.feat-my-branch\n",
+    )
+    .expected_stdout("[ROOT]/project.feat-my-branch\n")
+    .run()
+}
+
+#[test]
+fn create_sparse() -> Result {
+  let test = Test::new("project")?
+    .config("[sparse]\nweb = [\"apps/web\"]\napi = [\"apps/api\"]\n");
+
+  let project = test.tempdir.path().join("project");
+
+  fs::create_dir_all(project.join("apps/web"))?;
+  fs::create_dir_all(project.join("apps/api"))?;
+
+  let test = test
+    .commit("project", "apps/web/index.html", "web")
+    .commit("project", "apps/api/main.rs", "api")
+    .commit("project", "README.md", "readme");
+
+  let worktree = test.tempdir.path().join("project.feature");
+
+  let output = test.command(&["create", "--sparse", "web", "feature"])?;
+
+  assert_eq!(
+    str::from_utf8(&output.stderr)?,
+    "created worktree feature at project.feature\napplied sparse profile web\n",
+  );
+
+  assert!(worktree.join("apps/web/index.html").exists());
+  assert!(worktree.join("README.md").exists());
+  assert!(!worktree.join("apps/api").exists());
+
+  let test = test.directory("project.feature");
+
+  let output = test.command(&["sparse", "api"])?;
+
+  assert_eq!(
+    str::from_utf8(&output.stderr)?,
+    "applied sparse profile api to worktree feature\n",
+  );
+
+  assert!(worktree.join("apps/api/main.rs").exists());
+  assert!(!worktree.join("apps/web").exists());
+
+  test
+    .argument("list")
+    .argument("--template")
+    .argument("{branch} {sparse}")
+    .expected_stdout("main \nfeature api\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn create_submodules_survive_removal_of_borrowed_worktree() -> Result {
+  let test = Test::new("project")?.submodule("vendor/lib");
+
+  let root = test.tempdir.path().canonicalize()?;
+
+  fs::remove_dir_all(root.join("project/.git/modules"))?;
+
+  assert!(test.command(&["create", "first"])?.status.success());
+
+  assert!(test.command(&["create", "second"])?.status.success());
+
+  Test::git(
+    &root.join("project"),
+    &["worktree", "remove", "--force", "../project.first"],
+  );
+
+  assert!(!root.join("project/.git/worktrees/project.first").exists());
+
+  let fsck = Command::new("git")
+    .args(["fsck", "--no-dangling"])
+    .current_dir(root.join("project.second/vendor/lib"))
+    .output()?;
+
+  assert!(fsck.status.success());
+
+  assert_eq!(str::from_utf8(&fsck.stderr)?, "");
+
+  assert!(
+    !root
+      .join("project/.git/worktrees/project.second/modules/vendor/lib/objects/info/alternates")
+      .exists()
+  );
+
+  assert_eq!(
+    fs::read_to_string(root.join("project.second/vendor/lib/lib.txt"))?,
+    "lib"
+  );
+
+  Ok(())
+}
+
+#[test]
+fn create_unknown_sparse_profile() -> Result {
+  Test::new("project")?
+    .argument("create")
+    .argument("--sparse")
+    .argument("web")
+    .argument("feature")
+    .expected_status(1)
+    .expected_stderr("error: unknown sparse profile `web`\n")
+    .run()
+}
+
+#[test]
+fn delete() -> Result {
+  let test = Test::new("project")?;
+
+  let trash = test.tempdir.path().join(".wt-removing-1-0");
+
+  fs::create_dir_all(trash.join("nested"))?;
+
+  let output = test.command(&[
+    "delete",
+    "--log",
+    ".git/wt/delete.log",
+    "../.wt-removing-1-0",
+    "../.wt-removing-1-1",
+  ])?;
+
+  assert!(output.status.success());
+
+  assert!(!trash.exists());
+
+  assert!(
+    !test
+      .tempdir
+      .path()
+      .join("project/.git/wt/delete.log")
+      .exists()
+  );
+
+  Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn delete_failures_are_reported() -> Result {
+  let test = Test::new("project")?;
+
+  fs::create_dir_all(test.tempdir.path().join("project/.git/wt"))?;
+
+  fs::write(test.tempdir.path().join("file"), "")?;
+
+  let output =
+    test.command(&["delete", "--log", ".git/wt/delete.log", "../file"])?;
+
+  assert_eq!(
+    str::from_utf8(&output.stderr)?,
+    "error: failed to delete 1 directory\n"
+  );
+
+  let output = test.command(&["list", "--template", "{branch}"])?;
+
+  assert_eq!(str::from_utf8(&output.stderr)?, "");
+
+  test
+    .argument("create")
+    .argument("feature")
+    .expected_stdout("[ROOT]/project.feature\n")
+    .expected_stderr(indoc! {
+      "
+      warning: failed to delete `../file`: Not a directory (os error 20)
+      created worktree feature at project.feature
+      "
+    })
+    .run()
+}
+
+#[test]
+fn doctor() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .argument("doctor")
+    .expected_stderr("no problems found\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn doctor_broken_git_file() -> Result {
+  let test = Test::new("project")?.setup(&["create", "feature"]);
+
+  fs::write(
+    test.tempdir.path().join("project.feature/.git"),
+    "gitdir: /nowhere\n",
+  )?;
+
+  test
+    .argument("doctor")
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      "
+      problem: `.git` file in `[ROOT]/project.feature` points to `/nowhere` instead of `[ROOT]/project/.git/worktrees/project.feature`
+          fix: `git worktree repair [ROOT]/project.feature`
+      error: found 1 problem, run `wt repair` to fix it
+      "
+    })
+    .run()
+}
+
+#[test]
+fn doctor_finds_interrupted_conversion() -> Result {
+  let test = Test::new("project")?;
+
+  fs::write(test.workdir.join("file.txt"), "hello\n")?;
+
+  Test::git(&test.workdir, &["add", "file.txt"]);
+  Test::git(&test.workdir, &["commit", "-m", "add file.txt"]);
+
+  let staging = test.workdir.join(".wt-converting-1");
+
+  fs::create_dir(&staging)?;
+
+  fs::rename(test.workdir.join("file.txt"), staging.join("file.txt"))?;
+
+  fs::rename(test.workdir.join(".git"), test.workdir.join(".bare"))?;
+
+  fs::write(test.workdir.join(".git"), "gitdir: ./.bare\n")?;
+
+  Test::git(&test.workdir, &["config", "core.bare", "true"]);
+
+  let output = test.command(&["doctor"])?;
+
+  let stderr = str::from_utf8(&output.stderr)?;
+
+  assert!(
+    stderr.contains(
+      ".wt-converting-1` was left behind by an interrupted `convert-repo`"
+    ),
+    "{stderr}",
+  );
+
+  let output = test.command(&["repair"])?;
+
+  assert!(
+    output.status.success(),
+    "{}",
+    String::from_utf8_lossy(&output.stderr)
+  );
+
+  assert!(test.workdir.join(".git").is_dir());
+
+  assert!(!test.workdir.join(".bare").exists());
+
+  assert!(!staging.exists());
+
+  assert_eq!(
+    fs::read_to_string(test.workdir.join("file.txt"))?,
+    "hello\n"
+  );
+
+  let status = Command::new("git")
+    .args(["status", "--short"])
+    .current_dir(&test.workdir)
+    .output()?;
+
+  assert!(status.status.success());
+
+  assert_eq!(str::from_utf8(&status.stdout)?, "");
+
+  Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn doctor_moved_worktree() -> Result {
+  let test = Test::new("project")?.setup(&["create", "feature"]);
+
+  fs::rename(
+    test.tempdir.path().join("project.feature"),
+    test.tempdir.path().join("elsewhere"),
+  )?;
+
+  test
+    .argument("doctor")
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      "
+      problem: worktree `[ROOT]/project.feature` was moved to `[ROOT]/elsewhere`
+          fix: `git worktree repair [ROOT]/elsewhere`
+      error: found 1 problem, run `wt repair` to fix it
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn doctor_skips_locked_worktrees() -> Result {
+  let test = Test::new("project")?
+    .setup(&["create", "feature"])
+    .setup(&["lock", "feature"]);
+
+  fs::remove_dir_all(test.tempdir.path().join("project.feature"))?;
+
+  test
+    .argument("doctor")
+    .expected_stderr("no problems found\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn doctor_stale_worktree() -> Result {
+  let test = Test::new("project")?.setup(&["create", "feature"]);
+
+  fs::remove_dir_all(test.tempdir.path().join("project.feature"))?;
+
+  test
+    .argument("doctor")
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      "
+      problem: worktree `[ROOT]/project.feature` no longer exists, but `[ROOT]/project/.git/worktrees/project.feature` still refers to it
+          fix: `git worktree prune`
+      error: found 1 problem, run `wt repair` to fix it
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn doctor_trash() -> Result {
+  let test = Test::new("project")?;
+
+  fs::create_dir(test.tempdir.path().join(".wt-removing-1-0"))?;
+
+  test
+    .argument("doctor")
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      "
+      problem: `[ROOT]/.wt-removing-1-0` was left behind by an interrupted removal
+          fix: `rm -rf [ROOT]/.wt-removing-1-0`
+      error: found 1 problem, run `wt repair` to fix it
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn exec() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .argument("exec")
+    .argument("--")
+    .argument("echo out; echo err >&2")
+    .expected_stdout(indoc! {
+      "
+      main    │ out
+      feature │ out
+      "
+    })
+    .expected_stderr(indoc! {
+      "
+      main    │ err
+      feature │ err
+      main     ok
+      feature  ok
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn exec_arguments() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .argument("exec")
+    .argument("--")
+    .argument("git")
+    .argument("rev-parse")
+    .argument("--abbrev-ref")
+    .argument("HEAD")
+    .expected_stdout(indoc! {
+      "
+      main    │ main
+      feature │ feature
+      "
+    })
+    .expected_stderr(indoc! {
+      "
+      main     ok
+      feature  ok
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn exec_failure() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .argument("exec")
+    .argument("--")
+    .argument("test \"$(git branch --show-current)\" = main")
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      "
+      main     ok
+      feature  exit 1
+      error: 1 of 2 commands failed
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn exec_filter() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feat/one"])
+    .setup(&["create", "other"])
+    .argument("exec")
+    .argument("--filter")
+    .argument("feat/*")
+    .argument("--")
+    .argument("pwd")
+    .expected_stdout("feat/one │ [ROOT]/project.feat-one\n")
+    .expected_stderr("feat/one  ok\n")
+    .run()
+}
+
+#[test]
+fn exec_filter_no_match() -> Result {
+  Test::new("project")?
+    .argument("exec")
+    .argument("--filter")
+    .argument("nope*")
+    .argument("--")
+    .argument("true")
+    .expected_status(1)
+    .expected_stderr("error: no worktrees match `nope*`\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn exec_parallel() -> Result {
+  let test = Test::new("project")?
+    .setup(&["create", "feature"])
+    .setup(&["create", "other"]);
+
+  let output = test.command(&[
+    "exec",
+    "--parallel",
+    "3",
+    "--",
+    "git branch --show-current",
+  ])?;
+
+  assert!(output.status.success());
+
+  let mut lines = str::from_utf8(&output.stdout)?.lines().collect::<Vec<_>>();
+
+  lines.sort_unstable();
+
+  assert_eq!(
+    lines,
+    ["feature │ feature", "main    │ main", "other   │ other"],
+  );
+
+  Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn gc_finishes_interrupted_removal() -> Result {
+  let test = Test::new("project")?.setup(&["create", "feature"]);
+
+  fs::rename(
+    test.tempdir.path().join("project.feature"),
+    test.tempdir.path().join(".wt-removing-1-0"),
+  )?;
+
+  test
+    .journal(
+      i32::MAX.cast_unsigned(),
+      &[
+        ("path", "[ROOT]/project.feature"),
+        ("trash", "[ROOT]/.wt-removing-1-0"),
+        ("branch", "feature"),
+      ],
+    )
+    .setup(&["gc"])
+    .argument("list")
+    .argument("--template")
+    .argument("{branch}")
+    .expected_stdout("main\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn gc_finishes_interrupted_trashing() -> Result {
+  let test = Test::new("project")?.setup(&["create", "feature"]);
+
+  let root = test.tempdir.path().canonicalize()?;
+
+  let id = format!(
+    "{}-1-0",
+    std::time::SystemTime::now()
+      .duration_since(std::time::UNIX_EPOCH)?
+      .as_secs()
+  );
+
+  let directory = root.join("project/.git/wt/trash").join(&id);
+
+  fs::create_dir_all(&directory)?;
+
+  fs::copy(
+    root.join("project/.git/worktrees/project.feature/index"),
+    directory.join("index"),
+  )?;
+
+  fs::rename(root.join("project.feature"), directory.join("worktree"))?;
+
+  let trash = format!("[ROOT]/project/.git/wt/trash/{id}/worktree");
+
+  let head = Command::new("git")
+    .args(["rev-parse", "feature"])
+    .current_dir(root.join("project"))
+    .output()?
+    .stdout;
+
+  test
+    .journal(
+      i32::MAX.cast_unsigned(),
+      &[
+        ("path", "[ROOT]/project.feature"),
+        ("trash", &trash),
+        ("branch", "feature"),
+        ("head", str::from_utf8(&head)?.trim()),
+      ],
+    )
+    .setup(&["gc"])
+    .argument("restore")
+    .argument("feature")
+    .expected_stdout("[ROOT]/project.feature\n")
+    .expected_stderr("restored worktree feature at [ROOT]/project.feature\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn gc_nothing_to_clean_up() -> Result {
+  Test::new("project")?
+    .argument("gc")
+    .expected_stderr("nothing to clean up\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn gc_reports_recovery() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .journal(
+      i32::MAX.cast_unsigned(),
+      &[
+        ("path", "[ROOT]/project.gone"),
+        ("trash", "[ROOT]/.wt-removing-1-0"),
+      ],
+    )
+    .journal(
+      i32::MAX.cast_unsigned() - 1,
+      &[
+        ("path", "[ROOT]/project.feature"),
+        ("trash", "[ROOT]/.wt-removing-1-1"),
+        ("branch", "feature"),
+      ],
+    )
+    .argument("gc")
+    .expected_stderr(indoc! {
+      "
+      rolled back interrupted removal of worktree feature
+      finished interrupted removal of worktree (detached)
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn gc_rolls_back_interrupted_removal() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .journal(
+      i32::MAX.cast_unsigned(),
+      &[
+        ("path", "[ROOT]/project.feature"),
+        ("trash", "[ROOT]/.wt-removing-1-0"),
+        ("branch", "feature"),
+        ("locked", "usb drive"),
+      ],
+    )
+    .setup(&["gc"])
+    .argument("list")
+    .argument("--template")
+    .argument("{branch} {state}")
+    .expected_stdout("main \nfeature locked: usb drive\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn gc_rolls_back_interrupted_trashing() -> Result {
+  let test = Test::new("project")?.setup(&["create", "feature"]);
+
+  let directory = test.tempdir.path().join("project/.git/wt/trash/1-1-0");
+
+  fs::create_dir_all(&directory)?;
+
+  test
+    .journal(
+      i32::MAX.cast_unsigned(),
+      &[
+        ("path", "[ROOT]/project.feature"),
+        ("trash", "[ROOT]/project/.git/wt/trash/1-1-0/worktree"),
+        ("branch", "feature"),
+      ],
+    )
+    .setup(&["gc"])
+    .argument("restore")
+    .expected_stderr("trash is empty\n")
+    .exists(&["project.feature"])
+    .run()?;
+
+  assert!(!directory.exists());
+
+  Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn gc_skips_running_removals() -> Result {
+  Test::new("project")?
+    .journal(
+      std::process::id(),
+      &[
+        ("path", "[ROOT]/project.gone"),
+        ("trash", "[ROOT]/.wt-removing-1-0"),
+      ],
+    )
+    .argument("gc")
+    .expected_stderr("nothing to clean up\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn hook_dry_run() -> Result {
+  Test::new("project")?
+    .config(indoc! {
+      r#"
+      [[hooks.post_worktree_change]]
+      command = "nvm use"
+      only_if = ".nvmrc"
+      "#
+    })
+    .commit("project", ".nvmrc", "22")
+    .argument("hook")
+    .argument("post-worktree-change")
+    .argument("--dry-run")
+    .expected_stdout(indoc! {
+      "
+      post-worktree-change
+        nvm use
+          source: [ROOT]/.config/wt/config.toml
+          condition: only_if `.nvmrc`
+          runs: `.nvmrc` matched in [ROOT]/project
+      "
+    })
+    .run()
+}
+
+#[test]
+fn hook_skips_invalid_pattern() -> Result {
+  Test::new("project")?
+    .config(indoc! {
+      r#"
+      [[hooks.post_worktree_change]]
+      command = "make"
+      only_if = "[Makefile"
+
+      [[hooks.post_worktree_change]]
+      command = "direnv reload"
+      "#
+    })
+    .argument("hook")
+    .argument("post-worktree-change")
+    .expected_stdout("direnv reload\n")
+    .expected_stderr("warning: skipping hook `make`: invalid `only_if` pattern `[Makefile`: Pattern syntax error near position 0: invalid range pattern\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn hooks_list() -> Result {
+  Test::new("project")?
+    .config(indoc! {
+      r#"
+      [[hooks.post_worktree_change]]
+      command = "direnv reload"
+
+      [[hooks.post_worktree_change]]
+      command = "nvm use"
+      only_if = ".nvmrc"
+
+      [[hooks.post_worktree_change]]
+      command = "make"
+      only_if = "[Makefile"
+      "#
+    })
+    .argument("hooks")
+    .argument("list")
+    .expected_stdout(indoc! {
+      "
+      post-worktree-change
+        direnv reload
+          source: [ROOT]/.config/wt/config.toml
+          runs: no condition
+        nvm use
+          source: [ROOT]/.config/wt/config.toml
+          condition: only_if `.nvmrc`
+          skipped: `.nvmrc` matched no files in [ROOT]/project
+        make
+          source: [ROOT]/.config/wt/config.toml
+          condition: only_if `[Makefile`
+          skipped: invalid `only_if` pattern `[Makefile`: Pattern syntax error near position 0: invalid range pattern
+      "
+    })
+    .run()
+}
+
+#[test]
+fn hooks_list_without_hooks() -> Result {
+  Test::new("project")?
+    .argument("hooks")
+    .argument("list")
+    .expected_stdout("post-worktree-change\n  no hooks configured\n")
+    .run()
+}
+
+#[test]
+fn init_zsh() -> Result {
+  Test::new("project")?
+    .argument("init")
+    .argument("zsh")
+    .expected_stdout(include_str!("../src/subcommand/init.zsh"))
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn interrupted_removal_not_recovered_by_read_only_commands() -> Result {
+  let test = Test::new("project")?.setup(&["create", "feature"]);
+
+  fs::rename(
+    test.tempdir.path().join("project.feature"),
+    test.tempdir.path().join(".wt-removing-1-0"),
+  )?;
+
+  let test = test.journal(
+    i32::MAX.cast_unsigned(),
+    &[
+      ("path", "[ROOT]/project.feature"),
+      ("trash", "[ROOT]/.wt-removing-1-0"),
+      ("branch", "feature"),
+    ],
+  );
+
+  for arguments in [&["list", "--template", "{branch}"][..], &["doctor"]] {
+    test.command(arguments)?;
+  }
+
+  assert!(test.tempdir.path().join(".wt-removing-1-0").exists());
+
+  assert!(test.tempdir.path().join("project/.git/wt/journal").exists());
+
+  Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn interrupted_removal_recovered_on_next_create() -> Result {
+  let test = Test::new("project")?.setup(&["create", "feature"]);
+
+  fs::rename(
+    test.tempdir.path().join("project.feature"),
+    test.tempdir.path().join(".wt-removing-1-0"),
+  )?;
+
+  test
+    .journal(
+      i32::MAX.cast_unsigned(),
+      &[
+        ("path", "[ROOT]/project.feature"),
+        ("trash", "[ROOT]/.wt-removing-1-0"),
+        ("branch", "feature"),
+      ],
+    )
+    .argument("create")
+    .argument("other")
+    .expected_stdout("[ROOT]/project.other\n")
+    .expected_stderr(
+      "finished interrupted removal of worktree feature\ncreated worktree other at project.other\n",
+    )
+    .exists(&["project.other"])
+    .run()
+}
+
+#[test]
+fn list() -> Result {
+  Test::new("project")?
+    .argument("list")
+    .expected_stdout(
+      "* main  [HASH]  +0/-0  just now  Initial commit  [ROOT]/project\n",
+    )
+    .run()
+}
+
+#[test]
+fn list_after_create() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .argument("list")
+    .expected_stdout(indoc! {
+      "
+      * main     [HASH]  +0/-0  just now  Initial commit  [ROOT]/project
+        feature  [HASH]  +0/-0  just now  Initial commit  [ROOT]/project.feature
+      "
+    })
+    .run()
+}
+
+#[test]
+fn list_bare_repository() -> Result {
+  let test = Test::new("project")?;
+
+  Test::git(
+    test.tempdir.path(),
+    &["clone", "--bare", "project", "bare.git"],
+  );
+
+  Test::git(
+    &test.tempdir.path().join("bare.git"),
+    &["worktree", "add", "../checkout", "main"],
+  );
+
+  test
+    .directory("checkout")
+    .argument("list")
+    .expected_stdout(indoc! {
+      "
+        (bare)  unknown  +0/-0  just now  Initial commit  [ROOT]/bare.git
+      * main    [HASH]  +0/-0  just now  Initial commit  [ROOT]/checkout
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn list_cloned_layout() -> Result {
+  let test = Test::new("upstream")?.directory("");
+
+  let url = test.url("upstream");
+
+  test
+    .setup(&["clone", &url, "project"])
+    .directory("project/main")
+    .setup(&["create", "feature"])
+    .argument("list")
+    .argument("--path")
+    .argument("parent")
+    .argument("--sort")
+    .argument("name")
+    .argument("--template")
+    .argument("{branch}\t{path}")
+    .expected_stdout(indoc! {
+      "
+      (bare)\t.bare
+      feature\tfeature
+      main\tmain
+      "
+    })
+    .run()
+}
+
+#[test]
+fn list_columns_from_config() -> Result {
+  Test::new("project")?
+    .config(indoc! {
+      r#"
+      [list]
+      columns = ["branch", "marker", "path"]
+      "#
+    })
+    .setup(&["create", "feature"])
+    .argument("list")
+    .expected_stdout(indoc! {
+      "
+      main     * [ROOT]/project
+      feature    [ROOT]/project.feature
+      "
+    })
+    .run()
+}
+
+#[test]
+fn list_locked() -> Result {
+  let test = Test::new("project")?.setup(&["create", "feature"]);
+
+  Test::git(
+    &test.workdir,
+    &[
+      "worktree",
+      "lock",
+      "--reason",
+      "release",
+      "../project.feature",
+    ],
+  );
+
+  test
+    .argument("list")
+    .expected_stdout(indoc! {
+      "
+      * main     [HASH]  +0/-0  just now  Initial commit                   [ROOT]/project
+        feature  [HASH]  +0/-0  just now  Initial commit  locked: release  [ROOT]/project.feature
+      "
+    })
+    .run()
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn list_non_utf8_branch() -> Result {
+  use std::os::unix::ffi::OsStrExt;
+
+  let test = Test::new("project")?;
+
+  let output =
+    test.command(&[OsStr::new("create"), OsStr::from_bytes(b"f\xffo")])?;
+
+  assert!(output.status.success());
+
+  let output = test.command(&["list", "--template", "{branch}\t{path}"])?;
+
+  assert!(
+    output.status.success(),
+    "{}",
+    String::from_utf8_lossy(&output.stderr)
+  );
+
+  let root = test.tempdir.path().canonicalize()?;
+
+  assert_eq!(
+    output.stdout,
+    [
+      b"main\t".as_slice(),
+      root.join("project").as_os_str().as_bytes(),
+      b"\nf\xffo\t",
+      root
+        .join(OsStr::from_bytes(b"project.f\xffo"))
+        .as_os_str()
+        .as_bytes(),
+      b"\n",
+    ]
+    .concat(),
+  );
+
+  Ok(())
+}
+
+#[test]
+fn list_outside_git_repo() -> Result {
+  Test::without_git()?
+    .argument("list")
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      "
+      error: not a git repository
+             └─ fatal: not a git repository (or any of the parent directories): .git
+      "
+    })
+    .run()
+}
+
+#[test]
+fn list_path_home() -> Result {
+  Test::new("project")?
+    .argument("list")
+    .argument("--template")
+    .argument("{path}")
+    .argument("--path")
+    .argument("home")
+    .expected_stdout("~/project\n")
+    .run()
+}
+
+#[test]
+fn list_path_parent_from_config() -> Result {
+  Test::new("project")?
+    .config(indoc! {
+      r#"
+      [list]
+      columns = ["branch", "path"]
+      path = "parent"
+      "#
+    })
+    .setup(&["create", "feature"])
+    .argument("list")
+    .expected_stdout(indoc! {
+      "
+      main     project
+      feature  project.feature
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn list_path_with_newline() -> Result {
+  Test::new("pro\nject")?
+    .setup(&["create", "feature"])
+    .argument("list")
+    .argument("--template")
+    .argument("{branch}\t{path}")
+    .expected_stdout(indoc! {
+      "
+      main\t[ROOT]/pro
+      ject
+      feature\t[ROOT]/pro
+      ject.feature
+      "
+    })
+    .run()
+}
+
+#[test]
+fn list_prunable() -> Result {
+  let test = Test::new("project")?.setup(&["create", "feature"]);
+
+  fs::remove_dir_all(test.tempdir.path().join("project.feature"))?;
+
+  test
+    .argument("list")
+    .argument("--template")
+    .argument("{branch}\t{state}")
+    .expected_stdout(indoc! {
+      "
+      main\t
+      feature\tprunable: gitdir file points to non-existent location
+      "
+    })
+    .run()
+}
+
+#[test]
+fn list_sort_ahead() -> Result {
+  let test = Test::new("project")?
+    .setup(&["create", "feature"])
+    .setup(&["create", "other"]);
+
+  Test::git(
+    &test.tempdir.path().join("project.feature"),
+    &["commit", "--allow-empty", "-m", "feature work"],
+  );
+
+  test
+    .argument("list")
+    .argument("--sort")
+    .argument("ahead")
+    .expected_stdout(indoc! {
+      "
+      * main     [HASH]  +0/-0  just now  Initial commit  [ROOT]/project
+        other    [HASH]  +0/-0  just now  Initial commit  [ROOT]/project.other
+        feature  [HASH]  +0/-0  just now  feature work    [ROOT]/project.feature
+      "
+    })
+    .run()
+}
+
+#[test]
+fn list_sort_name() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .argument("list")
+    .argument("--sort")
+    .argument("name")
+    .expected_stdout(indoc! {
+      "
+        feature  [HASH]  +0/-0  just now  Initial commit  [ROOT]/project.feature
+      * main     [HASH]  +0/-0  just now  Initial commit  [ROOT]/project
+      "
+    })
+    .run()
+}
+
+#[test]
+fn list_template() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .argument("list")
+    .argument("--template")
+    .argument(r"{branch}\t{ahead}\t{path}")
+    .expected_stdout(indoc! {
+      "
+      main\t0\t[ROOT]/project
+      feature\t0\t[ROOT]/project.feature
+      "
+    })
+    .run()
+}
+
+#[test]
+fn list_template_unknown_column() -> Result {
+  Test::new("project")?
+    .argument("list")
+    .argument("--template")
+    .argument("{nope}")
+    .expected_status(2)
+    .expected_stderr(indoc! {
+      "
+      error: invalid value '{nope}' for '--template <TEMPLATE>': unknown column `nope`
+
+      For more information, try '--help'.
+      "
+    })
+    .run()
+}
+
+#[test]
+fn list_with_changes() -> Result {
+  let test = Test::new("project")?;
+
+  fs::write(test.workdir.join("file.txt"), "hello\n")?;
+
+  Test::git(&test.workdir, &["add", "file.txt"]);
+  Test::git(&test.workdir, &["commit", "-m", "add file.txt"]);
+
+  let test = test.setup(&["create", "feature"]);
+
+  fs::write(
+    test.tempdir.path().join("project.feature").join("file.txt"),
+    "hello\nworld\n",
+  )?;
+
+  test
+    .argument("list")
+    .expected_stdout(indoc! {
+      "
+      * main     [HASH]  +0/-0  just now  add file.txt  [ROOT]/project
+        feature  [HASH]  +1/-0  just now  add file.txt  [ROOT]/project.feature
+      "
+    })
+    .run()
+}
+
+#[test]
+fn lock() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .argument("lock")
+    .argument("feature")
+    .argument("--reason")
+    .argument("release branch")
+    .expected_stderr("locked worktree feature: release branch\n")
+    .exists(&["project/.git/worktrees/project.feature/locked"])
+    .run()
+}
+
+#[test]
+fn lock_current_worktree() -> Result {
+  let test = Test::new("project")?.setup(&["create", "feature"]);
+
+  fs::create_dir(test.tempdir.path().join("project.feature/src"))?;
+
+  test
+    .directory("project.feature/src")
+    .argument("lock")
+    .expected_stderr("locked worktree feature\n")
+    .exists(&["project/.git/worktrees/project.feature/locked"])
+    .run()
+}
+
+#[test]
+fn lock_shown_in_list() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .setup(&["lock", "feature", "--reason", "usb drive"])
+    .argument("list")
+    .argument("--template")
+    .argument("{branch} {state}")
+    .expected_stdout("main \nfeature locked: usb drive\n")
+    .run()
+}
+
+#[test]
+fn lock_twice() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .setup(&["lock", "feature"])
+    .argument("lock")
+    .argument("feature")
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      "
+      error: failed to lock worktree `feature`
+             └─ fatal: '[ROOT]/project.feature' is already locked
+      "
+    })
+    .run()
+}
+
+#[test]
+fn lock_unknown_branch() -> Result {
+  Test::new("project")?
+    .argument("lock")
+    .argument("nope")
+    .expected_status(1)
+    .expected_stderr("error: no worktree found for branch `nope`\n")
+    .run()
+}
+
+#[test]
+fn move_existing_destination() -> Result {
+  let test = Test::new("project")?.setup(&["create", "feature"]);
+
+  fs::create_dir(test.tempdir.path().join("project.renamed"))?;
+
+  test
+    .argument("move")
+    .argument("feature")
+    .argument("renamed")
+    .expected_status(1)
+    .expected_stderr("error: `[ROOT]/project.renamed` already exists\n")
+    .run()
+}
+
+#[test]
+fn move_from_inside() -> Result {
+  let test = Test::new("project")?.setup(&["create", "feature"]);
+
+  fs::create_dir(test.tempdir.path().join("project.feature/src"))?;
+
+  test
+    .directory("project.feature/src")
+    .argument("move")
+    .argument("feature")
+    .argument("feat/renamed")
+    .expected_stderr(
+      "moved worktree feature to feat/renamed at project.feat-renamed\n",
+    )
+    .expected_stdout("[ROOT]/project.feat-renamed/src\n")
+    .exists(&["project.feat-renamed/src"])
+    .run()
+}
+
+#[test]
+fn move_main_worktree() -> Result {
+  Test::new("project")?
+    .argument("move")
+    .argument("main")
+    .argument("trunk")
+    .expected_status(1)
+    .expected_stderr("error: cannot move the main worktree\n")
+    .run()
+}
+
+#[test]
+fn move_unknown_branch() -> Result {
+  Test::new("project")?
+    .argument("move")
+    .argument("nope")
+    .argument("renamed")
+    .expected_status(1)
+    .expected_stderr("error: no worktree found for branch `nope`\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn move_upstream() -> Result {
+  let test = Test::new("upstream")?.directory("");
+
+  let url = test.url("upstream");
+
+  let test = test.setup(&["clone", &url, "project"]).directory("project");
+
+  let output = test.command(&["move", "main", "trunk", "--upstream"])?;
+
+  assert_eq!(
+    str::from_utf8(&output.stderr)?,
+    "moved worktree main to trunk at trunk\nset upstream of trunk to origin/trunk\n",
+  );
+
+  let output = Command::new("git")
+    .args(["config", "branch.trunk.merge"])
+    .current_dir(test.tempdir.path().join("project/trunk"))
+    .env("GIT_CONFIG_GLOBAL", "/dev/null")
+    .env("GIT_CONFIG_SYSTEM", "/dev/null")
+    .output()?;
+
+  assert_eq!(str::from_utf8(&output.stdout)?, "refs/heads/trunk\n");
+
+  Ok(())
+}
+
+#[test]
+fn move_without_upstream() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .argument("move")
+    .argument("feature")
+    .argument("renamed")
+    .argument("--upstream")
+    .expected_status(1)
+    .expected_stderr("error: branch `feature` has no upstream\n")
+    .run()
+}
+
+#[test]
+fn move_worktree() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .argument("move")
+    .argument("feature")
+    .argument("renamed")
+    .expected_stderr("moved worktree feature to renamed at project.renamed\n")
+    .exists(&["project.renamed/.git"])
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn remove_bare_repository_without_checkouts() -> Result {
+  let test = Test::new("project")?;
+
+  Test::git(
+    test.tempdir.path(),
+    &["clone", "--bare", "project", "bare.git"],
+  );
+
+  test
+    .directory("bare.git")
+    .argument("remove")
+    .expected_status(1)
+    .expected_stderr("error: no worktrees to remove\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn remove_no_worktrees() -> Result {
+  Test::new("project")?
+    .argument("remove")
+    .expected_status(1)
+    .expected_stderr("error: no worktrees to remove\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn remove_only_locked_worktrees() -> Result {
+  let test = Test::new("project")?.setup(&["create", "feature"]);
+
+  Test::git(&test.workdir, &["worktree", "lock", "../project.feature"]);
+
+  test
+    .argument("remove")
+    .expected_status(1)
+    .expected_stderr(
+      "error: no worktrees to remove, 1 locked worktree can be removed with \
+       `--force`\n",
+    )
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn remove_outside_git_repo() -> Result {
+  Test::without_git()?
+    .argument("remove")
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      "
+      error: not a git repository
+             └─ fatal: not a git repository (or any of the parent directories): .git
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn repair() -> Result {
+  let test = Test::new("project")?
+    .setup(&["create", "moved"])
+    .setup(&["create", "stale"]);
+
+  let root = test.tempdir.path();
+
+  fs::rename(root.join("project.moved"), root.join("elsewhere"))?;
+  fs::remove_dir_all(root.join("project.stale"))?;
+  fs::create_dir(root.join(".wt-removing-1-0"))?;
+
+  test
+    .argument("repair")
+    .expected_stderr(indoc! {
+      "
+      fixed: worktree `[ROOT]/project.moved` was moved to `[ROOT]/elsewhere`
+      fixed: worktree `[ROOT]/project.stale` no longer exists, but `[ROOT]/project/.git/worktrees/project.stale` still refers to it
+      fixed: `[ROOT]/.wt-removing-1-0` was left behind by an interrupted removal
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn repair_leaves_no_problems() -> Result {
+  let test = Test::new("project")?
+    .setup(&["create", "moved"])
+    .setup(&["create", "stale"]);
+
+  let root = test.tempdir.path();
+
+  fs::rename(root.join("project.moved"), root.join("elsewhere"))?;
+  fs::remove_dir_all(root.join("project.stale"))?;
+  fs::create_dir(root.join(".wt-removing-1-0"))?;
+
+  test
+    .setup(&["repair"])
+    .argument("doctor")
+    .expected_stderr("no problems found\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn restore() -> Result {
+  let test = Test::new("project")?.setup(&["create", "feature"]).commit(
+    "project.feature",
+    "committed",
+    "foo",
+  );
+
+  fs::write(test.tempdir.path().join("project.feature/untracked"), "bar")?;
+
+  let test = test.trash("feature", 60);
+
+  let output = test.command(&["restore", "feature"])?;
+
+  assert_eq!(
+    str::from_utf8(&output.stderr)?,
+    format!(
+      "restored worktree feature at {}\n",
+      test
+        .tempdir
+        .path()
+        .canonicalize()?
+        .join("project.feature")
+        .display()
+    ),
+  );
+
+  let worktree = test.tempdir.path().join("project.feature");
+
+  assert_eq!(fs::read_to_string(worktree.join("committed"))?, "foo");
+  assert_eq!(fs::read_to_string(worktree.join("untracked"))?, "bar");
+
+  test
+    .argument("list")
+    .argument("--template")
+    .argument("{branch}")
+    .expected_stdout("main\nfeature\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn restore_existing_path() -> Result {
+  let test = Test::new("project")?
+    .setup(&["create", "feature"])
+    .trash("feature", 60);
+
+  fs::create_dir(test.tempdir.path().join("project.feature"))?;
+
+  test
+    .argument("restore")
+    .argument("feature")
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      "
+      error: failed to restore worktree `feature`
+             └─ `[ROOT]/project.feature` already exists
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn restore_gc_purges_expired_trash() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .trash("feature", 30 * 24 * 60 * 60)
+    .setup(&["gc"])
+    .argument("restore")
+    .expected_stderr("trash is empty\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn restore_list() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .trash("feature", 3 * 24 * 60 * 60)
+    .argument("restore")
+    .expected_stdout("feature  3d ago    [ROOT]/project.feature\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn restore_unknown_branch() -> Result {
+  Test::new("project")?
+    .argument("restore")
+    .argument("feature")
+    .expected_status(1)
+    .expected_stderr("error: no removed worktree found for branch `feature`\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn status() -> Result {
+  let test = Test::new("project")?
+    .config(indoc! {
+      r#"
+      [[hooks.post_worktree_change]]
+      command = "direnv reload"
+
+      [[hooks.post_worktree_change]]
+      command = "nvm use"
+      only_if = ".nvmrc"
+
+      [[hooks.post_worktree_change]]
+      command = "make"
+      only_if = "Makefile"
+      "#
+    })
+    .setup(&["create", "feature"])
+    .commit("project.feature", ".nvmrc", "22")
+    .commit("project", "main.txt", "main")
+    .setup(&["lock", "feature", "--reason", "wip"])
+    .directory("project.feature");
+
+  fs::write(test.tempdir.path().join("project.feature/.nvmrc"), "20")?;
+  fs::write(test.tempdir.path().join("project.feature/new"), "")?;
+
+  test
+    .argument("status")
+    .expected_stdout(indoc! {
+      "
+      branch    feature
+      upstream  none
+      default   main, 1 ahead, 1 behind
+      changes   0 staged, 1 unstaged, 1 untracked
+      lock      locked: wip
+      main      [ROOT]/project
+
+      post-worktree-change
+        direnv reload
+          source: [ROOT]/.config/wt/config.toml
+          runs: no condition
+        nvm use
+          source: [ROOT]/.config/wt/config.toml
+          condition: only_if `.nvmrc`
+          runs: `.nvmrc` matched in [ROOT]/project.feature
+        make
+          source: [ROOT]/.config/wt/config.toml
+          condition: only_if `Makefile`
+          skipped: `Makefile` matched no files in [ROOT]/project.feature
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn status_clean() -> Result {
+  Test::new("project")?
+    .argument("status")
+    .expected_stdout(indoc! {
+      "
+      branch    main
+      upstream  none
+      default   main, 0 ahead, 0 behind
+      changes   clean
+      lock      unlocked
+      main      [ROOT]/project
+
+      post-worktree-change
+        no hooks configured
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn status_compares_against_remote_default_branch() -> Result {
+  let test = Test::new("upstream")?;
+
+  let project = test.tempdir.path().join("project");
+
+  Test::git(test.tempdir.path(), &["clone", "upstream", "project"]);
+
+  Test::git(&project, &["checkout", "-b", "feature"]);
+
+  let test = test.commit("upstream", "main.txt", "main\n");
+
+  Test::git(&project, &["fetch", "origin"]);
+
+  test
+    .directory("project")
+    .argument("status")
+    .expected_stdout(indoc! {
+      "
+      branch    feature
+      upstream  none
+      default   origin/main, 0 ahead, 1 behind
+      changes   clean
+      lock      unlocked
+      main      [ROOT]/project
+
+      post-worktree-change
+        no hooks configured
+      "
+    })
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn switch_bare_repository_without_checkouts() -> Result {
+  let test = Test::new("project")?;
+
+  Test::git(
+    test.tempdir.path(),
+    &["clone", "--bare", "project", "bare.git"],
+  );
+
+  test
+    .directory("bare.git")
+    .argument("switch")
+    .expected_status(1)
+    .expected_stderr("error: no worktrees found\n")
+    .run()
+}
+
+#[cfg(unix)]
+#[test]
+fn switch_no_worktrees() -> Result {
+  Test::without_git()?
+    .argument("switch")
+    .expected_status(1)
+    .expected_stderr(indoc! {
+      "
+      error: not a git repository
+             └─ fatal: not a git repository (or any of the parent directories): .git
+      "
+    })
+    .run()
+}
+
+#[test]
+fn sync() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .commit("project.feature", "feature.txt", "feature\n")
+    .commit("project", "main.txt", "main\n")
+    .argument("sync")
+    .expected_stdout(indoc! {
+      "
+      main     up to date
+      feature  rebased     [HASH] → [HASH], 1 new commit
+      "
+    })
+    .run()
+}
+
+#[test]
+fn sync_base() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .setup(&["create", "other"])
+    .commit("project.other", "other.txt", "other\n")
+    .argument("sync")
+    .argument("--base")
+    .argument("other")
+    .expected_stdout(indoc! {
+      "
+      main     rebased     [HASH] → [HASH], 1 new commit
+      feature  rebased     [HASH] → [HASH], 1 new commit
+      other    up to date
+      "
+    })
+    .run()
+}
+
+#[test]
+fn sync_conflict() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .commit("project.feature", "file.txt", "feature\n")
+    .commit("project", "file.txt", "main\n")
+    .argument("sync")
+    .expected_status(1)
+    .expected_stdout(indoc! {
+      "
+      main     up to date
+      feature  conflict    1 conflicting file, resolve and run `git rebase --continue`
+      "
+    })
+    .expected_stderr("error: failed to sync 1 worktree\n")
+    .exists(&["project/.git/worktrees/project.feature/rebase-merge"])
+    .run()
+}
+
+#[test]
+fn sync_dirty() -> Result {
+  let test = Test::new("project")?
+    .setup(&["create", "feature"])
+    .commit("project.feature", "feature.txt", "feature\n")
+    .commit("project", "main.txt", "main\n");
+
+  fs::write(
+    test.tempdir.path().join("project.feature/feature.txt"),
+    "changed\n",
+  )?;
+
+  test
+    .argument("sync")
+    .expected_stdout(indoc! {
+      "
+      main     up to date
+      feature  skipped     uncommitted changes
+      "
+    })
+    .expected_stderr("warning: skipping `feature`: uncommitted changes\n")
+    .run()
+}
+
+#[test]
+fn sync_merge() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .commit("project.feature", "feature.txt", "feature\n")
+    .commit("project", "main.txt", "main\n")
+    .argument("sync")
+    .argument("--strategy")
+    .argument("merge")
+    .expected_stdout(indoc! {
+      "
+      main     up to date
+      feature  merged      [HASH] → [HASH], 1 new commit
+      "
+    })
+    .exists(&["project.feature/main.txt"])
+    .run()
+}
+
+#[test]
+fn sync_merge_config() -> Result {
+  Test::new("project")?
+    .config("[sync]\nstrategy = \"merge\"\n")
+    .setup(&["create", "feature"])
+    .commit("project", "main.txt", "main\n")
+    .argument("sync")
+    .expected_stdout(indoc! {
+      "
+      main     up to date
+      feature  merged      [HASH] → [HASH], 1 new commit
+      "
+    })
+    .run()
+}
+
+#[test]
+fn sync_onto_remote_default_branch() -> Result {
+  let test = Test::new("upstream")?;
+
+  let project = test.tempdir.path().join("project");
+
+  Test::git(test.tempdir.path(), &["clone", "upstream", "project"]);
+
+  Test::git(&project, &["config", "user.email", "test@test.com"]);
+  Test::git(&project, &["config", "user.name", "Test"]);
+  Test::git(&project, &["checkout", "-b", "feature"]);
+
+  let test = test
+    .directory("project")
+    .setup(&["create", "other"])
+    .commit("upstream", "main.txt", "main\n");
+
+  Test::git(&project, &["fetch", "origin"]);
+
+  test
+    .argument("sync")
+    .expected_stdout(indoc! {
+      "
+      feature  rebased  [HASH] → [HASH], 1 new commit
+      other    rebased  [HASH] → [HASH], 1 new commit
+      "
+    })
+    .run()
+}
+
+#[test]
+fn sync_unknown_base() -> Result {
+  Test::new("project")?
+    .argument("sync")
+    .argument("--base")
+    .argument("nope")
+    .expected_status(1)
+    .expected_stderr("error: unknown base `nope`\n")
+    .run()
+}
+
+#[test]
+fn unlock() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .setup(&["lock", "feature"])
+    .argument("unlock")
+    .argument("feature")
+    .expected_stderr("unlocked worktree feature\n")
+    .run()
+}
+
+#[test]
+fn unlock_not_locked() -> Result {
+  Test::new("project")?
+    .setup(&["create", "feature"])
+    .argument("unlock")
+    .argument("feature")
     .expected_status(1)
-    .expected_stderr("error: failed to list worktrees\n")
+    .expected_stderr("error: worktree `feature` is not locked\n")
     .run()
 }
 